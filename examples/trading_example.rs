@@ -1,117 +1,133 @@
-use anyhow::Result;
+// Worked examples for the router API (UNISWAP_TRADING.md), run against the active profile:
+//   cargo run --example trading_example -- <command> [args]
+// Needs WSS_URL and PRIVATE_KEY like the main binary; CONFIG_PATH and PROFILE pick the profile.
+
+use anyhow::{Result, anyhow};
 use ethers::prelude::*;
-use std::sync::Arc;
+use std::env;
 use tracing::info;
 
-// Import our modules
-use crate::uniswap::{UniswapTrader, GasConfig, quick_buy_token, parse_ether, get_deadline_from_now, quick_swap_tokens, quick_swap_tokens_with_approval};
+use rust_sniper_bot::config;
+use rust_sniper_bot::execution::{connect_signer, SignerClient};
+use rust_sniper_bot::uniswap::{UniswapTrader, GasConfig, quick_buy_token, parse_ether, get_deadline_from_now, quick_swap_tokens_with_approval};
+
+type Trader = UniswapTrader<SignerClient>;
+
+const USAGE: &str = "Usage: trading_example <command> [args]
+  info <token>                          Token name, symbol and wallet balance
+  buy <token> <eth_amount>              Quick buy with 5% slippage
+  buy-manual <token>                    0.1 ETH buy with custom EIP-1559 gas
+  sell <token> <amount>                 Sell tokens back to ETH
+  swap <token_in> <token_out> <amount>  Direct token-to-token swap
+  swap-via-weth <token_in> <token_out> <amount>
+  swap-manual <token_in> <token_out> <amount>
+  swap-path <amount> <token> <token>... Swap along a custom path";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    dotenv::dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    };
+    let arg = |index: usize| args.get(index).map(String::as_str).ok_or_else(|| anyhow!("Missing argument\n{}", USAGE));
+
+    let private_key = env::var("PRIVATE_KEY").map_err(|_| anyhow!("PRIVATE_KEY environment variable not set"))?;
+    let wss_url = env::var("WSS_URL").map_err(|_| anyhow!("WSS_URL environment variable not set"))?;
+    let profile = config::load_profile()?;
+
+    // One trader for every example; the router comes from the profile
+    let client = connect_signer(&wss_url, &private_key, profile.chain_id).await?;
+    let trader = UniswapTrader::with_router(client, profile.router).await?;
+
+    match command.as_str() {
+        "info" => check_token_info_example(&trader, arg(1)?).await,
+        "buy" => execute_trade_on_detection(&trader, arg(1)?, arg(2)?, 5.0).await,
+        "buy-manual" => manual_swap_example(&trader, arg(1)?).await,
+        "sell" => sell_tokens_example(&trader, arg(1)?, arg(2)?).await,
+        "swap" => token_to_token_swap_example(&trader, arg(1)?, arg(2)?, arg(3)?).await,
+        "swap-via-weth" => token_to_token_via_weth_example(&trader, arg(1)?, arg(2)?, arg(3)?).await,
+        "swap-manual" => manual_token_to_token_swap(&trader, arg(1)?, arg(2)?, arg(3)?).await,
+        "swap-path" => custom_path_token_swap(&trader, args.iter().skip(2).map(String::as_str).collect(), arg(1)?).await,
+        other => Err(anyhow!("Unknown command: {}\n{}", other, USAGE)),
+    }
+}
 
 // Example integration with token detection
-pub async fn execute_trade_on_detection(
+async fn execute_trade_on_detection(
+    trader: &Trader,
     token_address: &str,
-    private_key: &str,
-    rpc_url: &str,
     eth_amount: &str,
     slippage: f64
 ) -> Result<()> {
     info!("🚀 Executing trade for detected token: {}", token_address);
-    
-    // Setup wallet and provider
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-    
-    // Create Uniswap trader
-    let trader = UniswapTrader::new(client.clone())?;
-    
+
     // Parse addresses
     let token_addr: Address = token_address.parse()?;
-    let recipient = client.address();
-    
+    let recipient = trader.client().address();
+
     // Execute quick buy
     let receipt = quick_buy_token(
-        &trader,
+        trader,
         token_addr,
         eth_amount,
         slippage,
         recipient
     ).await?;
-    
+
     info!("✅ Trade executed successfully! Tx: {:?}", receipt.transaction_hash);
     Ok(())
 }
 
 // Example of manual swap with custom gas
-pub async fn manual_swap_example(
-    token_address: &str,
-    private_key: &str, 
-    rpc_url: &str
-) -> Result<()> {
-    // Setup
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-    let trader = UniswapTrader::new(client.clone())?;
-    
+async fn manual_swap_example(trader: &Trader, token_address: &str) -> Result<()> {
     // Addresses
     let token_addr: Address = token_address.parse()?;
-    let recipient = client.address();
-    
+    let recipient = trader.client().address();
+
     // Amount and timing
     let eth_amount = parse_ether("0.1")?; // 0.1 ETH
     let deadline = get_deadline_from_now(300); // 5 minutes
-    
+
     // Get quote first
-    let weth: Address = "0x4200000000000000000000000000000000000006".parse()?;
-    let path = vec![weth, token_addr];
+    let path = vec![trader.weth().await?, token_addr];
     let amounts_out = trader.get_amounts_out(eth_amount, path).await?;
     let expected_tokens = amounts_out[1];
-    
+
     // Calculate minimum with 5% slippage
     let min_tokens = expected_tokens * 95 / 100;
-    
+
     info!("💰 Expected: {}, Minimum: {}", expected_tokens, min_tokens);
-    
+
     // Custom gas configuration for speed
     let gas_config = GasConfig::new()
         .with_gas_limit(800_000)
         .with_eip1559_gas(10_000_000_000, 5_000_000_000); // 10 gwei max, 5 gwei priority
-    
+
     // Execute swap
     let receipt = trader.swap_eth_for_tokens(
         eth_amount,
-        token_addr, 
+        token_addr,
         min_tokens,
         recipient,
         deadline,
         Some(gas_config)
     ).await?;
-    
+
     info!("🎯 Swap completed! Block: {}", receipt.block_number.unwrap_or_default());
     Ok(())
 }
 
 // Example of selling tokens back to ETH
-pub async fn sell_tokens_example(
-    token_address: &str,
-    private_key: &str,
-    rpc_url: &str,
-    token_amount: &str
-) -> Result<()> {
-    // Setup  
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-    let trader = UniswapTrader::new(client.clone())?;
-    
+async fn sell_tokens_example(trader: &Trader, token_address: &str, token_amount: &str) -> Result<()> {
     // Parse values
     let token_addr: Address = token_address.parse()?;
-    let recipient = client.address();
+    let recipient = trader.client().address();
     let amount_to_sell: U256 = token_amount.parse()?;
-    
+
     // Check current allowance and approve if needed
     let current_allowance = trader.check_allowance(token_addr, recipient).await?;
     if current_allowance < amount_to_sell {
@@ -122,19 +138,18 @@ pub async fn sell_tokens_example(
             Some(GasConfig::fast())
         ).await?;
     }
-    
+
     // Get quote for selling
-    let weth: Address = "0x4200000000000000000000000000000000000006".parse()?;
-    let path = vec![token_addr, weth];
+    let path = vec![token_addr, trader.weth().await?];
     let amounts_out = trader.get_amounts_out(amount_to_sell, path).await?;
     let expected_eth = amounts_out[1];
-    
+
     // 3% slippage for selling
     let min_eth = expected_eth * 97 / 100;
     let deadline = get_deadline_from_now(300);
-    
+
     info!("💸 Selling {} tokens for minimum {} ETH", amount_to_sell, min_eth);
-    
+
     // Execute sell
     let receipt = trader.swap_tokens_for_eth(
         token_addr,
@@ -144,62 +159,46 @@ pub async fn sell_tokens_example(
         deadline,
         Some(GasConfig::turbo()) // Use turbo gas for selling
     ).await?;
-    
+
     info!("💰 Tokens sold! Tx: {:?}", receipt.transaction_hash);
     Ok(())
 }
 
-// Example of checking token info and balance
-pub async fn check_token_info_example(
-    token_address: &str,
-    wallet_address: &str,
-    rpc_url: &str
-) -> Result<()> {
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let client = Arc::new(provider);
-    let trader = UniswapTrader::new(client)?;
-    
+// Example of checking token info and the wallet's balance
+async fn check_token_info_example(trader: &Trader, token_address: &str) -> Result<()> {
     let token_addr: Address = token_address.parse()?;
-    let wallet_addr: Address = wallet_address.parse()?;
-    
+    let wallet_addr = trader.client().address();
+
     // Get token information
     let (name, symbol, decimals) = trader.get_token_info(token_addr).await?;
     info!("📋 Token: {} ({}) - {} decimals", name, symbol, decimals);
-    
+
     // Get balance
     let balance = trader.get_token_balance(token_addr, wallet_addr).await?;
     let formatted_balance = balance.as_u128() as f64 / 10f64.powi(decimals as i32);
     info!("💰 Balance: {:.6} {}", formatted_balance, symbol);
-    
+
     Ok(())
 }
 
 // Example of token-to-token swap (NO ETH involved)
-pub async fn token_to_token_swap_example(
+async fn token_to_token_swap_example(
+    trader: &Trader,
     token_in_address: &str,
     token_out_address: &str,
-    amount_in: &str,
-    private_key: &str,
-    rpc_url: &str
+    amount_in: &str
 ) -> Result<()> {
     info!("🔄 Executing token-to-token swap");
-    
-    // Setup
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-    let trader = UniswapTrader::new(client.clone())?;
-    
+
     // Parse addresses and amount
     let token_in: Address = token_in_address.parse()?;
     let token_out: Address = token_out_address.parse()?;
     let amount: U256 = amount_in.parse()?;
-    let recipient = client.address();
-    
+    let recipient = trader.client().address();
+
     // Method 1: Quick swap with automatic approval
     let receipt = quick_swap_tokens_with_approval(
-        &trader,
+        trader,
         token_in,
         token_out,
         amount,
@@ -207,37 +206,29 @@ pub async fn token_to_token_swap_example(
         recipient,
         false   // Direct swap (not via WETH)
     ).await?;
-    
+
     info!("✅ Token-to-token swap completed! Tx: {:?}", receipt.transaction_hash);
     Ok(())
 }
 
 // Example of multi-hop token swap via WETH
-pub async fn token_to_token_via_weth_example(
+async fn token_to_token_via_weth_example(
+    trader: &Trader,
     token_in_address: &str,
     token_out_address: &str,
-    amount_in: &str,
-    private_key: &str,
-    rpc_url: &str
+    amount_in: &str
 ) -> Result<()> {
     info!("🔄 Executing token-to-token swap via WETH");
-    
-    // Setup
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-    let trader = UniswapTrader::new(client.clone())?;
-    
+
     // Parse addresses and amount
     let token_in: Address = token_in_address.parse()?;
     let token_out: Address = token_out_address.parse()?;
     let amount: U256 = amount_in.parse()?;
-    let recipient = client.address();
-    
+    let recipient = trader.client().address();
+
     // Method 2: Multi-hop swap via WETH
     let receipt = quick_swap_tokens_with_approval(
-        &trader,
+        trader,
         token_in,
         token_out,
         amount,
@@ -245,34 +236,26 @@ pub async fn token_to_token_via_weth_example(
         recipient,
         true    // Via WETH routing
     ).await?;
-    
+
     info!("✅ Multi-hop token swap completed! Tx: {:?}", receipt.transaction_hash);
     Ok(())
 }
 
 // Example of manual token-to-token swap with custom gas
-pub async fn manual_token_to_token_swap(
+async fn manual_token_to_token_swap(
+    trader: &Trader,
     token_in_address: &str,
     token_out_address: &str,
-    amount_in: &str,
-    private_key: &str,
-    rpc_url: &str
+    amount_in: &str
 ) -> Result<()> {
     info!("🔄 Manual token-to-token swap with custom settings");
-    
-    // Setup
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-    let trader = UniswapTrader::new(client.clone())?;
-    
+
     // Parse addresses and amount
     let token_in: Address = token_in_address.parse()?;
     let token_out: Address = token_out_address.parse()?;
     let amount: U256 = amount_in.parse()?;
-    let recipient = client.address();
-    
+    let recipient = trader.client().address();
+
     // Check allowance and approve if needed
     let current_allowance = trader.check_allowance(token_in, recipient).await?;
     if current_allowance < amount {
@@ -283,21 +266,21 @@ pub async fn manual_token_to_token_swap(
             Some(GasConfig::fast())
         ).await?;
     }
-    
+
     // Get quote for direct swap
     let amounts_out = trader.get_token_to_token_quote(token_in, token_out, amount).await?;
     let expected_output = amounts_out[1];
-    
+
     // Calculate minimum with 2% slippage
     let min_output = expected_output * 98 / 100;
-    
+
     info!("💰 Expected: {}, Minimum: {}", expected_output, min_output);
-    
+
     // Custom gas for fastest execution
     let turbo_gas = GasConfig::new()
         .with_gas_limit(1_200_000)
         .with_eip1559_gas(25_000_000_000, 15_000_000_000); // Very high gas for speed
-    
+
     // Execute direct swap
     let receipt = trader.swap_exact_tokens_for_tokens_direct(
         token_in,
@@ -307,39 +290,27 @@ pub async fn manual_token_to_token_swap(
         recipient,
         Some(turbo_gas)
     ).await?;
-    
+
     info!("🎯 Manual token swap completed! Block: {}", receipt.block_number.unwrap_or_default());
     Ok(())
 }
 
 // Example of custom path token swap
-pub async fn custom_path_token_swap(
-    path: Vec<&str>,
-    amount_in: &str,
-    private_key: &str,
-    rpc_url: &str
-) -> Result<()> {
+async fn custom_path_token_swap(trader: &Trader, path: Vec<&str>, amount_in: &str) -> Result<()> {
     if path.len() < 2 {
-        return Err(anyhow::anyhow!("Path must contain at least 2 tokens"));
+        return Err(anyhow!("Path must contain at least 2 tokens"));
     }
-    
+
     info!("🔄 Custom path token swap with {} hops", path.len() - 1);
-    
-    // Setup
-    let provider = Provider::<Http>::try_from(rpc_url)?;
-    let wallet: LocalWallet = private_key.parse()?;
-    let client = SignerMiddleware::new(provider, wallet);
-    let client = Arc::new(client);
-    let trader = UniswapTrader::new(client.clone())?;
-    
+
     // Parse path and amount
     let parsed_path: Result<Vec<Address>> = path.iter()
-        .map(|addr| addr.parse())
+        .map(|addr| addr.parse::<Address>().map_err(Into::into))
         .collect();
     let token_path = parsed_path?;
     let amount: U256 = amount_in.parse()?;
-    let recipient = client.address();
-    
+    let recipient = trader.client().address();
+
     // Approve first token in path
     let token_in = token_path[0];
     let current_allowance = trader.check_allowance(token_in, recipient).await?;
@@ -351,16 +322,16 @@ pub async fn custom_path_token_swap(
             Some(GasConfig::fast())
         ).await?;
     }
-    
+
     // Get quote for custom path
     let amounts_out = trader.get_amounts_out(amount, token_path.clone()).await?;
     let expected_output = amounts_out[amounts_out.len() - 1];
-    
+
     // 4% slippage for complex paths
     let min_output = expected_output * 96 / 100;
-    
+
     info!("💰 Custom path - Expected: {}, Minimum: {}", expected_output, min_output);
-    
+
     // Execute custom path swap
     let receipt = trader.swap_tokens_custom_path(
         token_path,
@@ -369,31 +340,7 @@ pub async fn custom_path_token_swap(
         recipient,
         Some(GasConfig::turbo())
     ).await?;
-    
+
     info!("🎯 Custom path swap completed! Tx: {:?}", receipt.transaction_hash);
     Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
-    #[tokio::test]
-    async fn test_gas_configurations() {
-        // Test different gas configurations
-        let default_gas = GasConfig::default();
-        let fast_gas = GasConfig::fast();
-        let turbo_gas = GasConfig::turbo();
-        
-        println!("Default: {:?}", default_gas);
-        println!("Fast: {:?}", fast_gas);
-        println!("Turbo: {:?}", turbo_gas);
-        
-        // Test custom configuration
-        let custom = GasConfig::new()
-            .with_gas_limit(1_200_000)
-            .with_eip1559_gas(25_000_000_000, 15_000_000_000);
-        
-        println!("Custom: {:?}", custom);
-    }
-} 
\ No newline at end of file
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use futures_util::StreamExt;
+use tracing::{info, error};
+
+use rust_sniper_bot::config;
+use rust_sniper_bot::detection::DetectionEvent;
+use rust_sniper_bot::detector::TokenDetector;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    tracing_subscriber::fmt::init();
+    
+    // Load environment variables (matching JS require('dotenv').config())
+    dotenv::dotenv().ok();
+    
+    info!("🤖 Rust Sniper Bot - Starting...");
+    
+    // Create detector (matching JS global state initialization)
+    let profile = config::load_profile()?;
+    let detector = TokenDetector::new(profile)?;
+    
+    // CLI - Live detection only (matching JS if (require.main === module))
+    // Prints every detection until Ctrl-C
+    let mut detections = std::pin::pin!(detector.detections());
+    let mut shutdown = std::pin::pin!(tokio::signal::ctrl_c());
+    loop {
+        tokio::select! {
+            detection = detections.next() => match detection {
+                Some(Ok(DetectionEvent::Detected(token))) => println!("{}", token),
+                Some(Ok(DetectionEvent::Retracted(retraction))) => println!("RETRACTED {}", retraction.detection),
+                Some(Ok(DetectionEvent::Provisional(launch))) => println!("PENDING {}", launch),
+                Some(Ok(DetectionEvent::Enriched(detection))) => {
+                    if let Some(metadata) = &detection.metadata {
+                        println!("METADATA {:?} {}", detection.token, metadata);
+                    }
+                }
+                Some(Err(e)) => {
+                    error!("Detection failed: {}", e);
+                    std::process::exit(1);
+                }
+                None => break,
+            },
+            _ = &mut shutdown => {
+                info!("🛑 Shutdown requested");
+                detector.stop().await;
+                break;
+            }
+        }
+    }
+    
+    for stats in detector.endpoint_stats().await {
+        info!("🏁 {}", stats);
+    }
+    for stats in detector.cache_stats().await {
+        info!("🗃️ {}", stats);
+    }
+    
+    Ok(())
+}
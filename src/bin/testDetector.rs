@@ -0,0 +1,114 @@
+use anyhow::{Result, anyhow};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, error};
+
+use rust_sniper_bot::config::{self, DetectorOptions};
+use rust_sniper_bot::detection::DetectionEvent;
+use rust_sniper_bot::detector::TokenDetector;
+use rust_sniper_bot::recording::Recording;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    tracing_subscriber::fmt::init();
+    
+    // Load environment variables (matching JS require('dotenv').config())
+    dotenv::dotenv().ok();
+    
+    info!("🧪 Rust Sniper Bot - TEST MODE");
+    
+    // Parse command line arguments for testing
+    let args: Vec<String> = std::env::args().collect();
+    
+    if args.len() < 2 {
+        error!("❌ Usage: {} <block_number> OR <from_block> <to_block> OR --replay <capture.jsonl>", args[0]);
+        error!("   Example: {} 12345678", args[0]);
+        error!("   Example: {} 12345678 12345680", args[0]);
+        error!("   Example: {} --replay capture.jsonl", args[0]);
+        std::process::exit(1);
+    }
+    
+    // Offline: a RECORD_PATH capture through the live classification path, no WSS_URL needed
+    if args[1] == "--replay" {
+        let path = args.get(2).ok_or_else(|| anyhow!("--replay needs a capture file"))?;
+        let recording = Arc::new(Recording::from_file(Path::new(path))?);
+        let detector = TokenDetector::from_recording(config::load_profile()?, DetectorOptions::from_env(), recording.clone())?;
+        
+        let events = detector.replay(&recording).await?;
+        println!("🎯 REPLAYED {} EVENTS FROM {}:", events.len(), path);
+        for event in events {
+            match event {
+                DetectionEvent::Detected(detection) => println!("   {}", detection),
+                DetectionEvent::Provisional(launch) => println!("   🔮 {}", launch),
+                DetectionEvent::Enriched(detection) => {
+                    if let Some(metadata) = &detection.metadata {
+                        println!("   🏷️ {:?}: {}", detection.token, metadata);
+                    }
+                }
+                DetectionEvent::Retracted(retraction) => println!("   ↩️ {}", retraction.detection),
+            }
+        }
+        return Ok(());
+    }
+    
+    // Create detector (using same initialization as detector.rs)
+    let profile = config::load_profile()?;
+    let detector = TokenDetector::new(profile)?;
+    
+    if args.len() == 2 {
+        // Single block test
+        let block_number: u64 = args[1].parse()
+            .map_err(|_| anyhow!("Invalid block number: {}", args[1]))?;
+        
+        match detector.test_block(block_number).await {
+            Ok(tokens) => {
+                if !tokens.is_empty() {
+                    println!("🎯 DETECTED TOKENS:");
+                    for token in tokens {
+                        println!("   {}", token);
+                    }
+                } else {
+                    println!("🔍 No matching tokens found in block {}", block_number);
+                }
+            }
+            Err(e) => {
+                error!("❌ Test failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if args.len() == 3 {
+        // Block range test
+        let from_block: u64 = args[1].parse()
+            .map_err(|_| anyhow!("Invalid from_block: {}", args[1]))?;
+        let to_block: u64 = args[2].parse()
+            .map_err(|_| anyhow!("Invalid to_block: {}", args[2]))?;
+        
+        if from_block > to_block {
+            error!("❌ from_block ({}) cannot be greater than to_block ({})", from_block, to_block);
+            std::process::exit(1);
+        }
+        
+        match detector.test_block_range(from_block, to_block).await {
+            Ok(tokens) => {
+                if !tokens.is_empty() {
+                    println!("🎯 DETECTED TOKENS IN RANGE {} to {}:", from_block, to_block);
+                    for token in tokens {
+                        println!("   {}", token);
+                    }
+                } else {
+                    println!("🔍 No matching tokens found in block range {} to {}", from_block, to_block);
+                }
+            }
+            Err(e) => {
+                error!("❌ Test failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        error!("❌ Too many arguments. Usage: {} <block_number> OR <from_block> <to_block>", args[0]);
+        std::process::exit(1);
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use anyhow::{Result, anyhow};
+use std::env;
+use tracing::{info, warn, error};
+
+use rust_sniper_bot::config;
+use rust_sniper_bot::detector::TokenDetector;
+use rust_sniper_bot::execution::{connect_signer, execute_swap, snipe_live, SwapOutcome};
+use rust_sniper_bot::uniswap::UniswapTrader;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    // Load environment variables
+    dotenv::dotenv().ok();
+    
+    let private_key = env::var("PRIVATE_KEY")
+        .expect("PRIVATE_KEY environment variable not set");
+    let wss_url = env::var("WSS_URL")
+        .expect("WSS_URL environment variable not set");
+    let profile = config::load_profile()?;
+    
+    info!("🚀 Starting token detection and auto-swap system");
+    
+    let client = connect_signer(&wss_url, &private_key, profile.chain_id).await?;
+    let recipient = client.address();
+    
+    // Create Uniswap trader
+    let trader = UniswapTrader::with_router(client.clone(), profile.router).await?;
+    info!("✅ Uniswap trader initialized (WebSocket)");
+    
+    // Create token detector
+    let detector = TokenDetector::new(profile.clone())?;
+    info!("✅ Token detector initialized");
+    
+    // Parse command line arguments
+    let args: Vec<String> = env::args().collect();
+    
+    if args.len() == 1 {
+        // Live detection mode - no arguments provided
+        info!("🔴 LIVE DETECTION MODE - Waiting for real-time token deployments...");
+        
+        snipe_live(&detector, &trader, &profile, recipient).await?;
+        
+    } else if args.len() == 3 {
+        // Historical testing mode - block range provided
+        let from_block: u64 = args[1].parse()
+            .map_err(|_| anyhow!("Invalid from_block: {}", args[1]))?;
+        let to_block: u64 = args[2].parse()
+            .map_err(|_| anyhow!("Invalid to_block: {}", args[2]))?;
+        
+        if from_block > to_block {
+            error!("❌ from_block ({}) cannot be greater than to_block ({})", from_block, to_block);
+            std::process::exit(1);
+        }
+        
+        info!("🧪 HISTORICAL TEST MODE - Testing block range: {} to {}", from_block, to_block);
+        
+        // Test the block range and execute swaps for detected tokens
+        match detector.test_block_range(from_block, to_block).await {
+            Ok(detected_tokens) => {
+                if detected_tokens.is_empty() {
+                    info!("❌ No tokens detected in range");
+                    return Ok(());
+                }
+                
+                info!("🎯 Detected {} token(s), executing swaps...", detected_tokens.len());
+                
+                for detection in detected_tokens {
+                    match execute_swap(&trader, &profile, &detection, recipient).await {
+                        Ok(SwapOutcome::Sent(_)) => info!("✅ Swap completed for token: {:?}", detection.token),
+                        Ok(SwapOutcome::Skipped(reason)) => warn!("⏭️ Skipped token {:?}: {}", detection.token, reason),
+                        Err(e) => error!("❌ Swap failed for token {:?}: {}", detection.token, e),
+                    }
+                }
+            }
+            Err(e) => {
+                error!("❌ Block range test failed: {}", e);
+            }
+        }
+        
+    } else {
+        eprintln!("Usage:");
+        eprintln!("  {} - Start live detection mode", args[0]);
+        eprintln!("  {} <from_block> <to_block> - Test historical block range", args[0]);
+        std::process::exit(1);
+    }
+    
+    Ok(())
+}
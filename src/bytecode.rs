@@ -0,0 +1,297 @@
+use anyhow::{Result, anyhow};
+use ethers::prelude::*;
+use ethers::utils::id;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+const PUSH1: u8 = 0x60;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const JUMPDEST: u8 = 0x5b;
+const SLOAD: u8 = 0x54;
+const DELEGATECALL: u8 = 0xf4;
+const SELFDESTRUCT: u8 = 0xff;
+
+// keccak256("eip1967.proxy.implementation") - 1
+const EIP1967_IMPLEMENTATION_SLOT: &str = "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+// EIP-1167 minimal proxy: prefix, 20-byte implementation, suffix
+const MINIMAL_PROXY_PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+const MINIMAL_PROXY_SUFFIX: [u8; 15] = [0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3];
+
+// Rug mechanics a token's code can expose
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Risk {
+    Blacklist,
+    Pause,
+    OwnerMint,
+    AdjustableFees,
+    Selfdestruct,
+    // DELEGATECALL to a target read from storage, i.e. swappable logic
+    MutableDelegatecall,
+}
+
+// Function signatures whose selectors give a risk away
+const RISKY_FUNCTIONS: [(Risk, &str); 24] = [
+    (Risk::Blacklist, "blacklist(address)"),
+    (Risk::Blacklist, "addToBlacklist(address)"),
+    (Risk::Blacklist, "setBlacklist(address,bool)"),
+    (Risk::Blacklist, "blacklistAddress(address,bool)"),
+    (Risk::Blacklist, "addBot(address)"),
+    (Risk::Blacklist, "addBots(address[])"),
+    (Risk::Blacklist, "setBots(address[],bool)"),
+    (Risk::Pause, "pause()"),
+    (Risk::Pause, "setPaused(bool)"),
+    (Risk::Pause, "setTradingEnabled(bool)"),
+    (Risk::Pause, "setTrading(bool)"),
+    (Risk::OwnerMint, "mint(address,uint256)"),
+    (Risk::OwnerMint, "mint(uint256)"),
+    (Risk::OwnerMint, "mintTo(address,uint256)"),
+    (Risk::AdjustableFees, "setFee(uint256)"),
+    (Risk::AdjustableFees, "setFees(uint256,uint256)"),
+    (Risk::AdjustableFees, "setBuyFee(uint256)"),
+    (Risk::AdjustableFees, "setSellFee(uint256)"),
+    (Risk::AdjustableFees, "setTaxes(uint256,uint256)"),
+    (Risk::AdjustableFees, "setTaxFeePercent(uint256)"),
+    (Risk::AdjustableFees, "updateFees(uint256,uint256)"),
+    (Risk::AdjustableFees, "setSwapTaxes(uint256,uint256)"),
+    (Risk::AdjustableFees, "setProjectTaxRates(uint16,uint16)"),
+    (Risk::AdjustableFees, "setMaxTxAmount(uint256)"),
+];
+
+impl fmt::Display for Risk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Risk::Blacklist => "blacklist",
+            Risk::Pause => "pause",
+            Risk::OwnerMint => "owner_mint",
+            Risk::AdjustableFees => "adjustable_fees",
+            Risk::Selfdestruct => "selfdestruct",
+            Risk::MutableDelegatecall => "mutable_delegatecall",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub risk: Risk,
+    // Matched signature or opcode position
+    pub evidence: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskReport {
+    pub token: Address,
+    pub code_size: usize,
+    // Logic contract behind an EIP-1167 or EIP-1967 proxy, analyzed along with the token
+    pub implementation: Option<Address>,
+    pub findings: Vec<Finding>,
+}
+
+impl RiskReport {
+    pub fn risks(&self) -> BTreeSet<Risk> {
+        self.findings.iter().map(|f| f.risk).collect()
+    }
+
+    // Findings the policy refuses to trade with
+    pub fn vetoed_by(&self, policy: &[Risk]) -> Vec<&Finding> {
+        self.findings.iter().filter(|f| policy.contains(&f.risk)).collect()
+    }
+}
+
+impl fmt::Display for RiskReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {} bytes", self.token, self.code_size)?;
+        if let Some(implementation) = self.implementation {
+            write!(f, " (proxy for {:?})", implementation)?;
+        }
+        if self.findings.is_empty() {
+            return write!(f, ", no risky code found");
+        }
+        let risks: Vec<String> = self.risks().iter().map(Risk::to_string).collect();
+        write!(f, ", risks: {}", risks.join(", "))
+    }
+}
+
+// Solidity appends CBOR metadata whose bytes would decode as junk opcodes
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    let Some(&[hi, lo]) = code.len().checked_sub(2).map(|at| &code[at..]) else {
+        return code;
+    };
+    let len = u16::from_be_bytes([hi, lo]) as usize + 2;
+    match code.len().checked_sub(len) {
+        // CBOR map header (a1-a4) where the metadata should begin
+        Some(start) if (0xa1..=0xa4).contains(&code[start]) => &code[..start],
+        _ => code,
+    }
+}
+
+// Scan runtime code for risky selectors and opcodes. PUSH immediates are skipped,
+// so data that happens to contain 0xff or 0xf4 is not mistaken for an instruction.
+// A DELEGATECALL in a block that pushes the EIP-1967 implementation slot is the standard
+// upgradeable proxy; its implementation is analyzed separately, so it isn't flagged.
+pub fn analyze(code: &[u8]) -> Vec<Finding> {
+    let selectors: HashMap<[u8; 4], (Risk, &str)> = RISKY_FUNCTIONS
+        .iter()
+        .map(|&(risk, signature)| (id(signature), (risk, signature)))
+        .collect();
+    scan(code, &selectors)
+}
+
+fn scan(code: &[u8], selectors: &HashMap<[u8; 4], (Risk, &str)>) -> Vec<Finding> {
+    let implementation_slot: H256 = EIP1967_IMPLEMENTATION_SLOT.parse().expect("valid EIP-1967 slot");
+    let code = strip_metadata(code);
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut push = |risk, evidence: String| {
+        if !findings.iter().any(|f: &Finding| f.risk == risk && f.evidence == evidence) {
+            findings.push(Finding { risk, evidence });
+        }
+    };
+
+    let mut pc = 0;
+    // An SLOAD earlier in the current basic block, and whether the block pushed the EIP-1967 slot
+    let mut loaded_from_storage = false;
+    let mut proxy_slot = false;
+    while pc < code.len() {
+        let op = code[pc];
+        match op {
+            // Selectors with leading zero bytes are pushed with fewer bytes
+            PUSH1..=PUSH4 if pc + 1 + (op - PUSH1 + 1) as usize <= code.len() => {
+                let len = (op - PUSH1 + 1) as usize;
+                let mut selector = [0u8; 4];
+                selector[4 - len..].copy_from_slice(&code[pc + 1..pc + 1 + len]);
+                if let Some(&(risk, signature)) = selectors.get(&selector) {
+                    push(risk, signature.to_string());
+                }
+            }
+            PUSH32 if code.get(pc + 1..pc + 33) == Some(implementation_slot.as_bytes()) => proxy_slot = true,
+            JUMPDEST => (loaded_from_storage, proxy_slot) = (false, false),
+            SLOAD => loaded_from_storage = true,
+            DELEGATECALL if loaded_from_storage && !proxy_slot => {
+                push(Risk::MutableDelegatecall, format!("DELEGATECALL at 0x{:x}", pc));
+            }
+            SELFDESTRUCT => push(Risk::Selfdestruct, format!("SELFDESTRUCT at 0x{:x}", pc)),
+            _ => {}
+        }
+        pc += 1 + if (PUSH1..=PUSH32).contains(&op) { (op - PUSH1 + 1) as usize } else { 0 };
+    }
+
+    findings
+}
+
+// Implementation behind an EIP-1167 clone, read straight from its code
+fn minimal_proxy_target(code: &[u8]) -> Option<Address> {
+    let body = code.strip_prefix(&MINIMAL_PROXY_PREFIX[..])?;
+    if body.len() != 20 + MINIMAL_PROXY_SUFFIX.len() || !body[20..].starts_with(&MINIMAL_PROXY_SUFFIX) {
+        return None;
+    }
+    Some(Address::from_slice(&body[..20]))
+}
+
+// eth_getCode the token (and its implementation if it is a proxy) and analyze both
+pub async fn fetch_risk_report<M: Middleware + 'static>(client: &M, token: Address) -> Result<RiskReport> {
+    let code = client
+        .get_code(token, None)
+        .await
+        .map_err(|e| anyhow!("eth_getCode failed for {:?}: {}", token, e))?;
+    if code.is_empty() {
+        return Err(anyhow!("No code at {:?}", token));
+    }
+
+    let implementation = match minimal_proxy_target(&code) {
+        Some(target) => Some(target),
+        None => {
+            let slot: H256 = EIP1967_IMPLEMENTATION_SLOT.parse().expect("valid EIP-1967 slot");
+            let word = client
+                .get_storage_at(token, slot, None)
+                .await
+                .map_err(|e| anyhow!("eth_getStorageAt failed for {:?}: {}", token, e))?;
+            Some(Address::from(word)).filter(|a| !a.is_zero())
+        }
+    };
+
+    let mut findings = analyze(&code);
+    if let Some(implementation) = implementation {
+        let logic = client
+            .get_code(implementation, None)
+            .await
+            .map_err(|e| anyhow!("eth_getCode failed for implementation {:?}: {}", implementation, e))?;
+        findings.extend(analyze(&logic));
+    }
+
+    Ok(RiskReport {
+        token,
+        code_size: code.len(),
+        implementation,
+        findings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push4(signature: &str) -> Vec<u8> {
+        let mut op = vec![PUSH4];
+        op.extend(id(signature));
+        op
+    }
+
+    #[test]
+    fn test_finds_selectors_and_opcodes_but_not_push_data() {
+        let mut code = push4("setFee(uint256)");
+        code.extend(push4("transfer(address,uint256)"));
+        // SELFDESTRUCT and DELEGATECALL bytes inside a PUSH2 immediate
+        code.extend([0x61, SELFDESTRUCT, DELEGATECALL]);
+        // Constant target: PUSH20 <addr> DELEGATECALL
+        code.push(0x73);
+        code.extend([0x11; 20]);
+        code.push(DELEGATECALL);
+        // Target loaded from storage in a new block
+        code.extend([JUMPDEST, PUSH1, 0x00, SLOAD, DELEGATECALL, SELFDESTRUCT]);
+        // CBOR metadata containing 0xff
+        code.extend([0xa1, 0xff, 0xff, 0x00, 0x03]);
+
+        let findings = analyze(&code);
+        let risks: Vec<Risk> = findings.iter().map(|f| f.risk).collect();
+        assert_eq!(risks, vec![Risk::AdjustableFees, Risk::MutableDelegatecall, Risk::Selfdestruct]);
+        assert_eq!(findings[0].evidence, "setFee(uint256)");
+
+        let report = RiskReport { token: Address::zero(), code_size: code.len(), implementation: None, findings };
+        assert_eq!(report.vetoed_by(&[Risk::Selfdestruct, Risk::Blacklist]).len(), 1);
+        assert!(report.vetoed_by(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_short_pushed_selectors_and_eip1967_proxy() {
+        // solc pushes a selector with leading zero bytes as PUSH1-PUSH3
+        let selectors = HashMap::from([([0x00, 0x00, 0xab, 0xcd], (Risk::Blacklist, "zeroed()"))]);
+        let findings = scan(&[PUSH1 + 1, 0xab, 0xcd], &selectors);
+        assert_eq!(findings, vec![Finding { risk: Risk::Blacklist, evidence: "zeroed()".to_string() }]);
+        assert!(scan(&[PUSH4, 0xab, 0xcd, 0x00, 0x00], &selectors).is_empty());
+
+        // Standard proxy fallback: PUSH32 <implementation slot> SLOAD ... DELEGATECALL
+        let slot: H256 = EIP1967_IMPLEMENTATION_SLOT.parse().unwrap();
+        let mut proxy = vec![JUMPDEST, PUSH32];
+        proxy.extend(slot.as_bytes());
+        proxy.extend([SLOAD, DELEGATECALL]);
+        assert!(analyze(&proxy).is_empty());
+        // Any other slot is swappable logic
+        let mut custom = vec![JUMPDEST, PUSH32];
+        custom.extend([0x01; 32]);
+        custom.extend([SLOAD, DELEGATECALL]);
+        assert_eq!(analyze(&custom).first().map(|f| f.risk), Some(Risk::MutableDelegatecall));
+    }
+
+    #[test]
+    fn test_minimal_proxy_target() {
+        let mut code = MINIMAL_PROXY_PREFIX.to_vec();
+        code.extend([0x42; 20]);
+        code.extend(MINIMAL_PROXY_SUFFIX);
+        assert_eq!(minimal_proxy_target(&code), Some(Address::repeat_byte(0x42)));
+        assert_eq!(minimal_proxy_target(&code[..code.len() - 1]), None);
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+// Counters for one cache, reported on shutdown
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub len: usize,
+    pub capacity: usize,
+    // Least recently used entries dropped to make room
+    pub evicted: u64,
+    // Entries dropped because they outlived the TTL
+    pub expired: u64,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}/{} entries, {} evicted, {} expired",
+            self.name, self.len, self.capacity, self.evicted, self.expired)
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    inserted_at: Instant,
+    // Position in the recency order
+    tick: u64,
+}
+
+// Map with a size bound (least recently used goes first) and a per-entry TTL.
+// Entries are dropped one at a time, never wholesale.
+pub struct BoundedCache<K, V> {
+    name: &'static str,
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<K, Entry<V>>,
+    recency: BTreeMap<u64, K>,
+    next_tick: u64,
+    evicted: u64,
+    expired: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> BoundedCache<K, V> {
+    pub fn new(name: &'static str, capacity: usize, ttl: Duration) -> Self {
+        Self {
+            name,
+            capacity: capacity.max(1),
+            ttl,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            next_tick: 0,
+            evicted: 0,
+            expired: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_at(key, Instant::now())
+    }
+
+    pub fn contains(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Insert or replace; returns true if the key was not already live
+    pub fn insert(&mut self, key: K, value: V) -> bool {
+        self.insert_at(key, value, Instant::now())
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.tick);
+        Some(entry.value)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: self.name,
+            len: self.entries.len(),
+            capacity: self.capacity,
+            evicted: self.evicted,
+            expired: self.expired,
+        }
+    }
+
+    fn get_at(&mut self, key: &K, now: Instant) -> Option<&V> {
+        let expired = now.saturating_duration_since(self.entries.get(key)?.inserted_at) >= self.ttl;
+        if expired {
+            self.remove(key);
+            self.expired += 1;
+            return None;
+        }
+
+        let tick = self.bump();
+        let entry = self.entries.get_mut(key)?;
+        self.recency.remove(&entry.tick);
+        self.recency.insert(tick, key.clone());
+        entry.tick = tick;
+        Some(&entry.value)
+    }
+
+    fn insert_at(&mut self, key: K, value: V, now: Instant) -> bool {
+        let was_live = self.get_at(&key, now).is_some();
+        self.remove(&key);
+
+        self.expire_oldest(now);
+        while self.entries.len() >= self.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            self.evicted += 1;
+        }
+
+        let tick = self.bump();
+        self.recency.insert(tick, key.clone());
+        self.entries.insert(key, Entry { value, inserted_at: now, tick });
+        !was_live
+    }
+
+    // Drop stale entries from the cold end so they don't cost live ones their slot
+    fn expire_oldest(&mut self, now: Instant) {
+        while let Some((_, key)) = self.recency.first_key_value() {
+            let stale = self
+                .entries
+                .get(key)
+                .is_some_and(|e| now.saturating_duration_since(e.inserted_at) >= self.ttl);
+            if !stale {
+                break;
+            }
+            let key = key.clone();
+            self.remove(&key);
+            self.expired += 1;
+        }
+    }
+
+    fn bump(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used_one_at_a_time() {
+        let mut cache = BoundedCache::new("test", 2, Duration::from_secs(60));
+        assert!(cache.insert("a", 1));
+        assert!(cache.insert("b", 2));
+        assert!(!cache.insert("b", 3));
+
+        // Touch "a" so "b" is the coldest
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.insert("c", 4);
+
+        assert!(cache.contains(&"a"));
+        assert!(!cache.contains(&"b"));
+        assert!(cache.contains(&"c"));
+        assert_eq!(cache.stats().evicted, 1);
+        assert_eq!(cache.stats().len, 2);
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let mut cache = BoundedCache::new("test", 10, Duration::from_secs(5));
+        let start = Instant::now();
+        cache.insert_at("a", 1, start);
+        cache.insert_at("b", 2, start + Duration::from_secs(3));
+
+        assert_eq!(cache.get_at(&"a", start + Duration::from_secs(4)), Some(&1));
+        assert_eq!(cache.get_at(&"a", start + Duration::from_secs(5)), None);
+        // Reading does not refresh the TTL, only the recency
+        assert!(cache.insert_at("b", 3, start + Duration::from_secs(9)));
+
+        let stats = cache.stats();
+        assert_eq!((stats.expired, stats.evicted, stats.len), (2, 0, 1));
+    }
+}
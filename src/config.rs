@@ -0,0 +1,439 @@
+use anyhow::{Result, anyhow};
+use ethers::types::{Address, H256, U256};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::info;
+
+use crate::bytecode::Risk;
+use crate::strategy::DetectorConfig;
+
+// Default config location (override with CONFIG_PATH)
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+// Gas presets understood by GasConfig::preset
+pub const GAS_PRESETS: [&str; 3] = ["default", "fast", "turbo"];
+
+// Config file: a set of named profiles, one of which is active
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub default_profile: String,
+    pub profiles: HashMap<String, Profile>,
+}
+
+// Everything that changes between launch targets
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    #[serde(skip)]
+    pub name: String,
+    pub chain_id: u64,
+    pub deployer: Address,
+    pub topic: H256,
+    #[serde(default)]
+    pub wanted: Vec<Address>,
+    #[serde(default)]
+    pub unwanted: Vec<Address>,
+    pub router: Address,
+    pub input_token: Address,
+    // Human-readable amount of input_token, e.g. "10" or "0.001"
+    pub buy_amount: String,
+    #[serde(default = "default_decimals")]
+    pub input_decimals: u32,
+    #[serde(default = "default_gas_preset")]
+    pub gas_preset: String,
+    // Simulate buy, approve and sell before trading and skip tokens that fail it
+    #[serde(default = "default_honeypot_check")]
+    pub honeypot_check: bool,
+    // Largest sell tax (simulated sell below the router's quote) still considered tradable.
+    // Not a round-trip loss: restrictions that only apply after a buy are not simulated
+    #[serde(default = "default_honeypot_max_tax_bps")]
+    pub honeypot_max_tax_bps: u32,
+    // Input-token reserve the pool must hold before the buy is released, e.g. "1000"
+    #[serde(default = "default_min_liquidity")]
+    pub min_liquidity: String,
+    // How long to wait for the pool before giving up on a launch
+    #[serde(default = "default_liquidity_timeout_secs")]
+    pub liquidity_timeout_secs: u64,
+    // Bytecode findings that veto a buy; empty skips the analysis
+    #[serde(default = "default_veto_risks")]
+    pub veto_risks: Vec<Risk>,
+    // Which launches to buy; defaults to the wanted/unwanted creator lists
+    #[serde(default)]
+    pub detector: DetectorConfig,
+}
+
+fn default_decimals() -> u32 {
+    18
+}
+
+fn default_gas_preset() -> String {
+    "default".to_string()
+}
+
+fn default_honeypot_check() -> bool {
+    true
+}
+
+fn default_honeypot_max_tax_bps() -> u32 {
+    1_500
+}
+
+fn default_min_liquidity() -> String {
+    "0".to_string()
+}
+
+fn default_liquidity_timeout_secs() -> u64 {
+    120
+}
+
+// Fee setters are common on launchpad tokens and the honeypot check prices in current fees,
+// so they are reported but not vetoed by default
+fn default_veto_risks() -> Vec<Risk> {
+    vec![Risk::Blacklist, Risk::Pause, Risk::OwnerMint, Risk::Selfdestruct, Risk::MutableDelegatecall]
+}
+
+impl Config {
+    // Parse TOML or JSON depending on the file extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read config {}: {}", path.display(), e))?;
+
+        let config: Config = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&contents)
+                .map_err(|e| anyhow!("Invalid JSON config {}: {}", path.display(), e))?,
+            _ => toml::from_str(&contents)
+                .map_err(|e| anyhow!("Invalid TOML config {}: {}", path.display(), e))?,
+        };
+
+        Ok(config)
+    }
+
+    // Select and validate a profile by name
+    pub fn profile(&self, name: &str) -> Result<Profile> {
+        let mut profile = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Profile '{}' not found in config", name))?;
+        profile.name = name.to_string();
+        profile.validate()?;
+        Ok(profile)
+    }
+}
+
+impl Profile {
+    pub fn validate(&self) -> Result<()> {
+        if self.chain_id == 0 {
+            return Err(anyhow!("[{}] chain_id must be non-zero", self.name));
+        }
+        self.detector
+            .validate()
+            .map_err(|e| anyhow!("[{}] invalid detector: {}", self.name, e))?;
+        if self.wanted.is_empty() && self.detector.uses_creator_list() {
+            return Err(anyhow!("[{}] at least one wanted creator is required", self.name));
+        }
+        if let Some(addr) = self.wanted.iter().find(|a| self.unwanted.contains(a)) {
+            return Err(anyhow!("[{}] {:?} is both wanted and unwanted", self.name, addr));
+        }
+        for (field, addr) in [
+            ("deployer", self.deployer),
+            ("router", self.router),
+            ("input_token", self.input_token),
+        ] {
+            if addr == Address::zero() {
+                return Err(anyhow!("[{}] {} must not be the zero address", self.name, field));
+            }
+        }
+        if self.topic == H256::zero() {
+            return Err(anyhow!("[{}] topic must not be zero", self.name));
+        }
+        if self.buy_amount()?.is_zero() {
+            return Err(anyhow!("[{}] buy_amount must be greater than zero", self.name));
+        }
+        self.min_liquidity()?;
+        if self.liquidity_timeout_secs == 0 {
+            return Err(anyhow!("[{}] liquidity_timeout_secs must be greater than zero", self.name));
+        }
+        if self.honeypot_max_tax_bps > 10_000 {
+            return Err(anyhow!("[{}] honeypot_max_tax_bps must be at most 10000", self.name));
+        }
+        if !GAS_PRESETS.contains(&self.gas_preset.as_str()) {
+            return Err(anyhow!(
+                "[{}] unknown gas_preset '{}' (expected one of {:?})",
+                self.name, self.gas_preset, GAS_PRESETS
+            ));
+        }
+        Ok(())
+    }
+
+    // Buy size in input token base units
+    pub fn buy_amount(&self) -> Result<U256> {
+        let amount = ethers::utils::parse_units(&self.buy_amount, self.input_decimals)
+            .map_err(|e| anyhow!("[{}] invalid buy_amount '{}': {}", self.name, self.buy_amount, e))?;
+        Ok(amount.into())
+    }
+
+    // Minimum pool reserve of input_token in base units
+    pub fn min_liquidity(&self) -> Result<U256> {
+        let amount = ethers::utils::parse_units(&self.min_liquidity, self.input_decimals)
+            .map_err(|e| anyhow!("[{}] invalid min_liquidity '{}': {}", self.name, self.min_liquidity, e))?;
+        Ok(amount.into())
+    }
+
+    pub fn is_wanted(&self, addr: &Address) -> bool {
+        self.wanted.contains(addr)
+    }
+
+    pub fn is_unwanted(&self, addr: &Address) -> bool {
+        self.unwanted.contains(addr)
+    }
+}
+
+// Runtime switches read from the environment, independent of the launch target
+#[derive(Debug, Clone)]
+pub struct DetectorOptions {
+    // Resolve the tx sender when the event alone cannot classify a launch
+    pub use_tx_verification: bool,
+    // Also watch pending factory calls and emit provisional detections
+    pub use_mempool: bool,
+    // Read token metadata via Multicall3 after each detection is emitted, as a follow-up Enriched event
+    pub enrich_metadata: bool,
+    // Append every inbound WebSocket message to this JSONL file, for TokenDetector::replay
+    pub record_path: Option<PathBuf>,
+    pub caches: CacheLimits,
+    pub verification: VerifyLimits,
+}
+
+impl Default for DetectorOptions {
+    fn default() -> Self {
+        Self {
+            use_tx_verification: true,
+            use_mempool: false,
+            enrich_metadata: false,
+            record_path: None,
+            caches: CacheLimits::default(),
+            verification: VerifyLimits::default(),
+        }
+    }
+}
+
+// Sizes and lifetime of the detector's dedupe and verification caches
+#[derive(Debug, Clone)]
+pub struct CacheLimits {
+    // Launch logs (and pending txs) already handled
+    pub processed_txs: usize,
+    // Resolved tx senders
+    pub callers: usize,
+    // Txs whose sender is known not to be wanted
+    pub rejected_callers: usize,
+    pub ttl: Duration,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            processed_txs: 10_000,
+            callers: 5_000,
+            rejected_callers: 5_000,
+            ttl: Duration::from_secs(6 * 60 * 60),
+        }
+    }
+}
+
+impl CacheLimits {
+    // PROCESSED_TX_CACHE_SIZE, CALLER_CACHE_SIZE, REJECTED_CACHE_SIZE and CACHE_TTL_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            processed_txs: env_number("PROCESSED_TX_CACHE_SIZE").unwrap_or(defaults.processed_txs),
+            callers: env_number("CALLER_CACHE_SIZE").unwrap_or(defaults.callers),
+            rejected_callers: env_number("REJECTED_CACHE_SIZE").unwrap_or(defaults.rejected_callers),
+            ttl: env_number("CACHE_TTL_SECS")
+                .map(|secs| Duration::from_secs(secs as u64))
+                .unwrap_or(defaults.ttl),
+        }
+    }
+}
+
+impl DetectorOptions {
+    // USE_TX_VERIFICATION (default: true), USE_MEMPOOL and ENRICH_METADATA (default: false), RECORD_PATH
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            use_tx_verification: env_flag("USE_TX_VERIFICATION", defaults.use_tx_verification),
+            use_mempool: env_flag("USE_MEMPOOL", defaults.use_mempool),
+            enrich_metadata: env_flag("ENRICH_METADATA", defaults.enrich_metadata),
+            record_path: std::env::var("RECORD_PATH").ok().filter(|p| !p.is_empty()).map(PathBuf::from),
+            caches: CacheLimits::from_env(),
+            verification: VerifyLimits::from_env(),
+        }
+    }
+}
+
+fn env_flag(name: &str, default: bool) -> bool {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(default)
+}
+
+// Where verify_caller looks for the launch initiator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerifyMode {
+    // eth_getTransactionByHash().from only
+    #[default]
+    Sender,
+    // Receipt, then debug_traceTransaction call frames when the tx did not call the factory directly
+    // (smart wallets, multisigs, bundlers)
+    Trace,
+}
+
+impl std::str::FromStr for VerifyMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sender" => Ok(VerifyMode::Sender),
+            "trace" => Ok(VerifyMode::Trace),
+            _ => Err(anyhow!("unknown verify mode '{}' (expected sender or trace)", s)),
+        }
+    }
+}
+
+// How hard verify_caller tries before settling on Unknown
+#[derive(Debug, Clone)]
+pub struct VerifyLimits {
+    pub mode: VerifyMode,
+    // Deadline for each lookup attempt (all of its requests together)
+    pub timeout: Duration,
+    // Further attempts after the first inconclusive one
+    pub retries: u32,
+    pub retry_delay: Duration,
+}
+
+impl Default for VerifyLimits {
+    fn default() -> Self {
+        Self {
+            mode: VerifyMode::default(),
+            timeout: Duration::from_millis(1_500),
+            retries: 2,
+            retry_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl VerifyLimits {
+    // VERIFY_MODE, VERIFY_TIMEOUT_MS, VERIFY_RETRIES and VERIFY_RETRY_DELAY_MS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            mode: std::env::var("VERIFY_MODE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.mode),
+            timeout: env_number("VERIFY_TIMEOUT_MS")
+                .map(|ms| Duration::from_millis(ms as u64))
+                .unwrap_or(defaults.timeout),
+            retries: std::env::var("VERIFY_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.retries),
+            retry_delay: env_number("VERIFY_RETRY_DELAY_MS")
+                .map(|ms| Duration::from_millis(ms as u64))
+                .unwrap_or(defaults.retry_delay),
+        }
+    }
+}
+
+// Positive integer from the environment; anything else falls back to the default
+fn env_number(name: &str) -> Option<usize> {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+}
+
+// Load the active profile using CONFIG_PATH and PROFILE from the environment
+pub fn load_profile() -> Result<Profile> {
+    let path = std::env::var("CONFIG_PATH").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+    let config = Config::from_file(Path::new(&path))?;
+    let name = std::env::var("PROFILE").unwrap_or_else(|_| config.default_profile.clone());
+    let profile = config.profile(&name)?;
+
+    info!("📄 Loaded profile '{}' from {} (chain {})", profile.name, path, profile.chain_id);
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+        default_profile = "base"
+
+        [profiles.base]
+        chain_id = 8453
+        deployer = "0x71B8EFC8BCaD65a5D9386D07f2Dff57ab4EAf533"
+        topic = "0xf9d151d23a5253296eb20ab40959cf48828ea2732d337416716e302ed83ca658"
+        wanted = ["0x81F7cA6AF86D1CA6335E44A2C28bC88807491415"]
+        unwanted = ["0x03Fb99ea8d3A832729a69C3e8273533b52f30D1A"]
+        router = "0x4752ba5dbc23f44d87826276bf6fd6b1c372ad24"
+        input_token = "0x0b3e328455c4059eeb9e3f84b5543f74e24e7e1b"
+        buy_amount = "0.001"
+        gas_preset = "fast"
+    "#;
+
+    #[test]
+    fn test_parse_and_validate_profile() {
+        let config: Config = toml::from_str(SAMPLE).unwrap();
+        let profile = config.profile("base").unwrap();
+        assert_eq!(profile.name, "base");
+        assert_eq!(profile.buy_amount().unwrap(), U256::from(1_000_000_000_000_000u64));
+        assert!(profile.is_wanted(&"0x81F7cA6AF86D1CA6335E44A2C28bC88807491415".parse().unwrap()));
+        assert!(config.profile("missing").is_err());
+    }
+
+    #[test]
+    fn test_shipped_config_is_valid() {
+        let config = Config::from_file(Path::new("config.toml")).unwrap();
+        for name in config.profiles.keys() {
+            config.profile(name).unwrap();
+        }
+        config.profile(&config.default_profile).unwrap();
+    }
+
+    #[test]
+    fn test_parse_verify_mode() {
+        assert_eq!("trace".parse::<VerifyMode>().unwrap(), VerifyMode::Trace);
+        assert_eq!("Sender".parse::<VerifyMode>().unwrap(), VerifyMode::Sender);
+        assert!("receipt".parse::<VerifyMode>().is_err());
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let config: Config = toml::from_str(SAMPLE).unwrap();
+        let base = config.profile("base").unwrap();
+
+        let mut overlap = base.clone();
+        overlap.unwanted.push(overlap.wanted[0]);
+        assert!(overlap.validate().is_err());
+
+        let mut no_wanted = base.clone();
+        no_wanted.wanted.clear();
+        assert!(no_wanted.validate().is_err());
+
+        let mut no_wanted_needed = base.clone();
+        no_wanted_needed.wanted.clear();
+        no_wanted_needed.detector = DetectorConfig::NewPair;
+        assert!(no_wanted_needed.validate().is_ok());
+
+        let mut bad_gas = base.clone();
+        bad_gas.gas_preset = "warp".to_string();
+        assert!(bad_gas.validate().is_err());
+
+        let mut zero_buy = base;
+        zero_buy.buy_amount = "0".to_string();
+        assert!(zero_buy.validate().is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, H256};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::launch_event::{LaunchCall, LaunchEvent};
+use crate::metadata::TokenMetadata;
+
+// How much an accepted launch is backed by the wanted list, from the rule that decided it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Confidence {
+    // A wanted creator appears in the launch event
+    Wanted,
+    // The launch tx's sender or initiator is a wanted creator
+    Verified,
+    // Nothing was checked (trust mode)
+    Unverified,
+    // Accepted by a heuristic strategy, with no wanted creator involved
+    Heuristic,
+}
+
+impl From<MatchRule> for Confidence {
+    fn from(rule: MatchRule) -> Self {
+        match rule {
+            MatchRule::EventAddress => Confidence::Wanted,
+            MatchRule::VerifiedCaller | MatchRule::PendingSender => Confidence::Verified,
+            MatchRule::TrustMode => Confidence::Unverified,
+            MatchRule::Reputation | MatchRule::NewPair => Confidence::Heuristic,
+        }
+    }
+}
+
+// Why a launch was accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MatchRule {
+    // A wanted creator is one of the launch event's addresses
+    EventAddress,
+    // The launch initiator is a wanted creator (verify_caller)
+    VerifiedCaller,
+    // Accepted unverified because USE_TX_VERIFICATION=false
+    TrustMode,
+    // A wanted creator sent a pending launch call to the factory
+    PendingSender,
+    // Scored addresses reached the reputation threshold
+    Reputation,
+    // The launch event names a pool (new-pair watcher)
+    NewPair,
+}
+
+// How verify_caller found the account behind a launch tx
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum InitiatorPath {
+    // eth_getTransactionByHash().from
+    TxSender,
+    // Receipt shows the tx called the factory directly, so its sender is the initiator
+    DirectCall,
+    // debug_traceTransaction frame into the factory; `chain` runs from tx.from to the factory's caller
+    CallFrame { chain: Vec<Address> },
+    // Relayed tx that could not be traced; the receipt sender is the best available answer
+    ReceiptSender { reason: String },
+}
+
+impl fmt::Display for InitiatorPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitiatorPath::TxSender => write!(f, "tx sender"),
+            InitiatorPath::DirectCall => write!(f, "receipt, direct factory call"),
+            InitiatorPath::CallFrame { chain } => {
+                write!(f, "trace ")?;
+                for addr in chain {
+                    write!(f, "{:?} -> ", addr)?;
+                }
+                write!(f, "factory")
+            }
+            InitiatorPath::ReceiptSender { reason } => write!(f, "receipt sender, untraced: {}", reason),
+        }
+    }
+}
+
+// The account that actually invoked the factory, and how it was resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Initiator {
+    pub address: Address,
+    pub path: InitiatorPath,
+}
+
+// Outcome of resolving who launched a token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Verification {
+    // Launched by a wanted creator
+    Verified(Initiator),
+    // Launched by anyone else
+    Rejected(Initiator),
+    // No definitive answer (timeout, transport error, tx not visible yet); never cached
+    Unknown(String),
+}
+
+// Where a log sits on chain; block fields are absent for pending logs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogPosition {
+    pub block_number: Option<u64>,
+    pub block_hash: Option<H256>,
+    pub tx_hash: H256,
+    pub log_index: Option<u64>,
+    // Only some nodes include blockTimestamp in logs
+    pub block_timestamp: Option<u64>,
+}
+
+impl LogPosition {
+    pub fn from_log(log: &Value) -> Result<Self> {
+        let tx_hash = log["transactionHash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing transaction hash"))?
+            .parse::<H256>()
+            .map_err(|e| anyhow!("Invalid transaction hash: {}", e))?;
+
+        Ok(Self {
+            block_number: log["blockNumber"].as_str().and_then(parse_hex_u64),
+            block_hash: log["blockHash"].as_str().and_then(|h| h.parse().ok()),
+            tx_hash,
+            log_index: log["logIndex"].as_str().and_then(parse_hex_u64),
+            block_timestamp: log["blockTimestamp"].as_str().and_then(parse_hex_u64),
+        })
+    }
+}
+
+// Accepted launch, with enough context to trace and audit the decision
+#[derive(Debug, Clone, Serialize)]
+pub struct Detection {
+    pub chain_id: u64,
+    pub token: Address,
+    pub event: LaunchEvent,
+    #[serde(flatten)]
+    pub position: LogPosition,
+    // Address that earned the match: an event participant, or the caller for caller rules
+    pub matched_address: Option<Address>,
+    // Account that initiated the launch tx; only known once verify_caller has resolved it
+    pub caller: Option<Address>,
+    // Set when the caller came from verify_caller
+    pub caller_path: Option<InitiatorPath>,
+    pub confidence: Confidence,
+    pub rule: MatchRule,
+    // Local clock: when the log arrived, was classified and when the decision was made
+    pub received_at: DateTime<Utc>,
+    pub classified_at: DateTime<Utc>,
+    pub decided_at: DateTime<Utc>,
+    // Multicall3 read of the token: set before the decision for strategies that want_metadata,
+    // otherwise only on the Enriched event that follows Detected with ENRICH_METADATA
+    pub metadata: Option<TokenMetadata>,
+}
+
+impl Detection {
+    // Time spent classifying and verifying
+    pub fn decision_latency(&self) -> chrono::Duration {
+        self.decided_at - self.received_at
+    }
+}
+
+impl fmt::Display for Detection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} (block {}, tx {:?}, rule {:?}",
+            self.token,
+            self.position.block_number.map_or("pending".to_string(), |b| b.to_string()),
+            self.position.tx_hash,
+            self.rule)?;
+        if let Some(matched) = self.matched_address.filter(|m| Some(*m) != self.caller) {
+            write!(f, ", matched {:?}", matched)?;
+        }
+        if let Some(caller) = self.caller {
+            write!(f, ", caller {:?}", caller)?;
+        }
+        if let Some(path) = &self.caller_path {
+            write!(f, " via {}", path)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// A previously emitted detection whose log was removed by a reorg
+#[derive(Debug, Clone, Serialize)]
+pub struct Retraction {
+    pub detection: Detection,
+    pub retracted_at: DateTime<Utc>,
+}
+
+// Launch predicted from a pending factory call, before any log exists.
+// predicted_token is unverified: it is the factory's next CREATE address and is
+// wrong whenever another factory call is mined ahead of this one. Only the mined
+// log's Detected event carries the real token.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingLaunch {
+    pub chain_id: u64,
+    pub tx_hash: H256,
+    pub creator: Address,
+    pub call: LaunchCall,
+    pub predicted_token: Address,
+    pub rule: MatchRule,
+    pub received_at: DateTime<Utc>,
+    pub decided_at: DateTime<Utc>,
+}
+
+impl fmt::Display for PendingLaunch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} (unverified prediction, pending tx {:?}, {} #{}, creator {:?})",
+            self.predicted_token,
+            self.tx_hash,
+            self.call.function,
+            self.call.application_id,
+            self.creator)
+    }
+}
+
+// What the live detector yields to executors
+#[derive(Debug, Clone, Serialize)]
+pub enum DetectionEvent {
+    Detected(Detection),
+    Retracted(Retraction),
+    // Only produced in mempool mode; the mined log follows as Detected
+    Provisional(PendingLaunch),
+    // Only with ENRICH_METADATA: an earlier Detected again, with its metadata attached
+    Enriched(Detection),
+}
+
+// Blocks behind the newest tracked detection that can still be reorged out
+const REORG_WINDOW: u64 = 64;
+
+// Emitted detections indexed by block hash, so removed logs can be matched back
+#[derive(Debug, Default)]
+pub struct ReorgTracker {
+    by_block: HashMap<H256, Vec<Detection>>,
+}
+
+impl ReorgTracker {
+    // Remember a mined detection; pending ones have no block hash and are skipped
+    pub fn track(&mut self, detection: &Detection) {
+        let (Some(hash), Some(number)) = (detection.position.block_hash, detection.position.block_number) else {
+            return;
+        };
+
+        self.by_block.entry(hash).or_default().push(detection.clone());
+        self.by_block.retain(|_, detections| {
+            detections
+                .iter()
+                .any(|d| d.position.block_number.is_some_and(|b| b + REORG_WINDOW >= number))
+        });
+    }
+
+    // Take back the detection for a removed log, if we emitted one
+    pub fn retract(&mut self, position: &LogPosition) -> Option<Detection> {
+        let hash = position.block_hash?;
+        let detections = self.by_block.get_mut(&hash)?;
+        let index = detections.iter().position(|d| {
+            d.position.tx_hash == position.tx_hash && d.position.log_index == position.log_index
+        })?;
+
+        let detection = detections.remove(index);
+        if detections.is_empty() {
+            self.by_block.remove(&hash);
+        }
+        Some(detection)
+    }
+}
+
+pub fn parse_hex_u64(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_from_mined_and_pending_logs() {
+        let mined = serde_json::json!({
+            "blockNumber": "0x1a",
+            "blockHash": format!("{:?}", H256::repeat_byte(0xbb)),
+            "transactionHash": format!("{:?}", H256::repeat_byte(0xaa)),
+            "logIndex": "0x3"
+        });
+        let position = LogPosition::from_log(&mined).unwrap();
+        assert_eq!(position.block_number, Some(26));
+        assert_eq!(position.block_hash, Some(H256::repeat_byte(0xbb)));
+        assert_eq!(position.tx_hash, H256::repeat_byte(0xaa));
+        assert_eq!(position.log_index, Some(3));
+
+        let pending = serde_json::json!({
+            "blockNumber": null,
+            "transactionHash": format!("{:?}", H256::repeat_byte(0xaa))
+        });
+        let position = LogPosition::from_log(&pending).unwrap();
+        assert_eq!(position.block_number, None);
+        assert_eq!(position.log_index, None);
+
+        assert!(LogPosition::from_log(&serde_json::json!({})).is_err());
+    }
+
+    fn detection(block: u64, hash: u8, tx: u8) -> Detection {
+        let event = LaunchEvent {
+            virtual_id: 1.into(),
+            token: Address::repeat_byte(tx),
+            dao: Address::zero(),
+            tba: Address::zero(),
+            ve_token: Address::zero(),
+            pair: Address::zero(),
+        };
+        Detection {
+            chain_id: 8453,
+            token: event.token,
+            event,
+            position: LogPosition {
+                block_number: Some(block),
+                block_hash: Some(H256::repeat_byte(hash)),
+                tx_hash: H256::repeat_byte(tx),
+                log_index: Some(0),
+                block_timestamp: None,
+            },
+            matched_address: None,
+            caller: None,
+            caller_path: None,
+            confidence: MatchRule::EventAddress.into(),
+            rule: MatchRule::EventAddress,
+            received_at: Utc::now(),
+            classified_at: Utc::now(),
+            decided_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_confidence_follows_the_deciding_rule() {
+        assert_eq!(Confidence::from(MatchRule::EventAddress), Confidence::Wanted);
+        assert_eq!(Confidence::from(MatchRule::VerifiedCaller), Confidence::Verified);
+        assert_eq!(Confidence::from(MatchRule::TrustMode), Confidence::Unverified);
+        // No wanted creator behind these
+        assert_eq!(Confidence::from(MatchRule::Reputation), Confidence::Heuristic);
+        assert_eq!(Confidence::from(MatchRule::NewPair), Confidence::Heuristic);
+    }
+
+    #[test]
+    fn test_reorg_tracker_retracts_by_block_hash() {
+        let mut tracker = ReorgTracker::default();
+        let first = detection(100, 0x01, 0xa1);
+        let second = detection(100, 0x01, 0xa2);
+        tracker.track(&first);
+        tracker.track(&second);
+
+        // Same tx in a different (canonical) block is not ours to retract
+        let mut other_block = first.position.clone();
+        other_block.block_hash = Some(H256::repeat_byte(0x02));
+        assert!(tracker.retract(&other_block).is_none());
+
+        assert_eq!(tracker.retract(&first.position).unwrap().token, first.token);
+        assert!(tracker.retract(&first.position).is_none());
+        assert_eq!(tracker.retract(&second.position).unwrap().token, second.token);
+    }
+
+    #[test]
+    fn test_reorg_tracker_forgets_blocks_outside_window() {
+        let mut tracker = ReorgTracker::default();
+        let old = detection(100, 0x01, 0xa1);
+        let recent = detection(100 + REORG_WINDOW + 1, 0x02, 0xa2);
+        tracker.track(&old);
+        tracker.track(&recent);
+
+        assert!(tracker.retract(&old.position).is_none());
+        assert!(tracker.retract(&recent.position).is_some());
+    }
+}
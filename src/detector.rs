@@ -1,493 +1,1547 @@
 use anyhow::{Result, anyhow};
-use futures_util::{SinkExt, StreamExt};
-use regex::Regex;
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, Bytes};
+use futures_util::Stream;
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use tracing::{info, error};
-
-// Configuration (matching JS exactly)
-const TARGET_TOPIC: &str = "0xf9d151d23a5253296eb20ab40959cf48828ea2732d337416716e302ed83ca658";
-const DEPLOYER: &str = "0x71B8EFC8BCaD65a5D9386D07f2Dff57ab4EAf533";
-const WANTED: &str = "0x81F7cA6AF86D1CA6335E44A2C28bC88807491415";
-const UNWANTED: &str = "0x03Fb99ea8d3A832729a69C3e8273533b52f30D1A";
-
-// Pre-compiled patterns (matching JS)
-const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
-
-#[derive(Debug, Clone)]
-enum Confidence {
-    Wanted,
-    Unwanted,
-    Verify,
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, Mutex};
+use tracing::{info, warn, error};
+
+use crate::cache::{BoundedCache, CacheStats};
+use crate::config::{DetectorOptions, Profile, VerifyMode};
+use crate::detection::{parse_hex_u64, Detection, DetectionEvent, Initiator, InitiatorPath, LogPosition, MatchRule, PendingLaunch, ReorgTracker, Retraction, Verification};
+use crate::launch_event::{LaunchCall, LaunchEvent};
+use crate::metadata::{self, TokenMetadata};
+use crate::race::{EndpointRace, EndpointStats};
+use crate::recording::{Recorder, Recording};
+use crate::rpc::{RpcClient, RpcError, Subscription};
+use crate::scan::{self, ScanOptions};
+use crate::strategy::{Candidate, Decision, LaunchDetector};
+
+// Reconnect backoff for the live subscription
+const RECONNECT_BASE_DELAY_MS: u64 = 500;
+const RECONNECT_MAX_DELAY_MS: u64 = 30_000;
+
+// Detections buffered while the consumer is busy (e.g. sending a swap)
+const DETECTION_BUFFER: usize = 64;
+
+// Metadata is nice to have; never hold a detection longer than this for it
+const METADATA_TIMEOUT_MS: u64 = 1_000;
+
+// WSS_URL; serves the historical block tests
+const PRIMARY_ENDPOINT: usize = 0;
+
+// One raced WebSocket endpoint and its lazily (re)opened connection
+struct Endpoint {
+    url: String,
+    rpc: Mutex<Option<Arc<RpcClient>>>,
 }
 
-#[derive(Debug, Clone)]
-struct TokenResult {
-    token: String,
-    confidence: Confidence,
+// How a single subscription session ended
+enum SessionEnd {
+    Stopped,
+    Rejected(String),
 }
 
-// Global state (matching JS)
+// Global state (matching JS) - clones share the same state
+#[derive(Clone)]
 pub struct TokenDetector {
-    wss_url: String,
-    use_tx_verification: bool,
-    should_stop: Arc<Mutex<bool>>,
-    processed_txs: Arc<Mutex<HashSet<String>>>,
-    caller_cache: Arc<Mutex<HashMap<String, String>>>,
-    rejected_callers: Arc<Mutex<HashSet<String>>>,
-    address_regex: Regex,
-    wanted_hex: String,
-    unwanted_hex: String,
+    endpoints: Arc<Vec<Endpoint>>,
+    race: Arc<Mutex<EndpointRace>>,
+    options: DetectorOptions,
+    // Set by stop(); live sessions wait on it alongside their sockets
+    stop: Arc<watch::Sender<bool>>,
+    processed_txs: Arc<Mutex<BoundedCache<String, ()>>>,
+    pending_txs: Arc<Mutex<BoundedCache<String, ()>>>,
+    caller_cache: Arc<Mutex<BoundedCache<String, Initiator>>>,
+    rejected_callers: Arc<Mutex<BoundedCache<String, Initiator>>>,
+    last_block: Arc<Mutex<Option<u64>>>,
+    reorgs: Arc<Mutex<ReorgTracker>>,
+    strategy: Arc<dyn LaunchDetector>,
+    recorder: Option<Recorder>,
+    profile: Profile,
 }
 
 impl TokenDetector {
-    pub fn new() -> Result<Self> {
+    pub fn new(profile: Profile) -> Result<Self> {
         // Load WSS_URL from environment (matching JS)
         let wss_url = std::env::var("WSS_URL")
             .map_err(|_| anyhow!("WSS_URL environment variable not set"))?;
         
-        // Load USE_TX_VERIFICATION from environment (default: true)
-        let use_tx_verification = std::env::var("USE_TX_VERIFICATION")
-            .unwrap_or_else(|_| "true".to_string())
-            .parse::<bool>()
-            .unwrap_or(true);
         
-        info!("🔧 Transaction verification: {}", if use_tx_verification { "ENABLED" } else { "DISABLED" });
+        // Optional extra endpoints raced against WSS_URL (comma separated)
+        let mut wss_urls = vec![wss_url];
+        if let Ok(extra) = std::env::var("WSS_URLS") {
+            wss_urls.extend(extra.split(',').map(str::trim).filter(|url| !url.is_empty()).map(String::from));
+        }
         
-        // Pre-compiled regex (matching JS addressRegex)
-        let address_regex = Regex::new(r"000000000000000000000000([a-fA-F0-9]{40})")
-            .map_err(|e| anyhow!("Failed to compile regex: {}", e))?;
+        Self::with_options(profile, wss_urls, DetectorOptions::from_env())
+    }
+    
+    // Explicit endpoints and switches, bypassing the environment.
+    // The first endpoint also serves historical queries.
+    pub fn with_options(profile: Profile, wss_urls: Vec<String>, options: DetectorOptions) -> Result<Self> {
+        if wss_urls.is_empty() || wss_urls.len() > EndpointRace::MAX_ENDPOINTS {
+            return Err(anyhow!("Expected 1 to {} WebSocket endpoints, got {}", EndpointRace::MAX_ENDPOINTS, wss_urls.len()));
+        }
         
-        // Pre-computed hex values (matching JS)
-        let wanted_hex = WANTED[2..].to_lowercase(); // Remove 0x prefix
-        let unwanted_hex = UNWANTED[2..].to_lowercase(); // Remove 0x prefix
+        info!("🔧 Transaction verification: {}", if options.use_tx_verification { "ENABLED" } else { "DISABLED" });
+        info!("🔧 Caller verification mode: {:?}", options.verification.mode);
+        info!("🔧 Mempool detection: {}", if options.use_mempool { "ENABLED" } else { "DISABLED" });
+        info!("🔧 Metadata enrichment: {}", if options.enrich_metadata { "ENABLED" } else { "DISABLED" });
+        info!("🔧 WebSocket endpoints: {}", wss_urls.len());
+        info!("🔧 Launch detector: {:?}", profile.detector);
         
+        // The decoder only understands the NewPersona layout
+        if profile.topic != LaunchEvent::topic() {
+            return Err(anyhow!("Unsupported launch topic {:?} in profile '{}'", profile.topic, profile.name));
+        }
+        
+        let recorder = match &options.record_path {
+            Some(path) => {
+                info!("🔧 Recording WebSocket traffic to {}", path.display());
+                Some(Recorder::create(path)?)
+            }
+            None => None,
+        };
+        
+        let limits = options.caches.clone();
         Ok(Self {
-            wss_url,
-            use_tx_verification,
-            should_stop: Arc::new(Mutex::new(false)),
-            processed_txs: Arc::new(Mutex::new(HashSet::new())),
-            caller_cache: Arc::new(Mutex::new(HashMap::new())),
-            rejected_callers: Arc::new(Mutex::new(HashSet::new())),
-            address_regex,
-            wanted_hex,
-            unwanted_hex,
+            race: Arc::new(Mutex::new(EndpointRace::new(&wss_urls))),
+            endpoints: Arc::new(wss_urls
+                .into_iter()
+                .map(|url| Endpoint { url, rpc: Mutex::new(None) })
+                .collect()),
+            options,
+            stop: Arc::new(watch::channel(false).0),
+            processed_txs: Arc::new(Mutex::new(BoundedCache::new("processed_txs", limits.processed_txs, limits.ttl))),
+            pending_txs: Arc::new(Mutex::new(BoundedCache::new("pending_txs", limits.processed_txs, limits.ttl))),
+            caller_cache: Arc::new(Mutex::new(BoundedCache::new("caller_cache", limits.callers, limits.ttl))),
+            rejected_callers: Arc::new(Mutex::new(BoundedCache::new("rejected_callers", limits.rejected_callers, limits.ttl))),
+            last_block: Arc::new(Mutex::new(None)),
+            reorgs: Arc::new(Mutex::new(ReorgTracker::default())),
+            strategy: Arc::from(profile.detector.build(&profile.wanted, &profile.unwanted)),
+            recorder,
+            profile,
         })
     }
+    
+    // Offline detector whose single endpoint answers from a capture; drive it with replay()
+    pub fn from_recording(profile: Profile, mut options: DetectorOptions, recording: Arc<Recording>) -> Result<Self> {
+        options.record_path = None;
+        let mut detector = Self::with_options(profile, vec!["replay".to_string()], options)?;
+        detector.endpoints = Arc::new(vec![Endpoint {
+            url: "replay".to_string(),
+            rpc: Mutex::new(Some(RpcClient::replay(recording))),
+        }]);
+        Ok(detector)
+    }
 
-    // Extract token and determine caller in one pass (matching JS extractTokenAndCaller)
-    fn extract_token_and_caller(&self, data: &str) -> Option<TokenResult> {
-        if data.is_empty() || data.len() < 130 {
-            return None;
+    // Shared JSON-RPC connection to one endpoint, reopened if the previous socket has closed
+    async fn rpc(&self, index: usize) -> Result<Arc<RpcClient>> {
+        let endpoint = &self.endpoints[index];
+        let mut rpc = endpoint.rpc.lock().await;
+        if let Some(client) = rpc.as_ref() {
+            if !client.is_closed() {
+                return Ok(client.clone());
+            }
         }
         
-        let mut addresses = Vec::new();
+        let recorder = self.recorder.as_ref().map(|r| r.for_endpoint(index));
+        let client = RpcClient::connect_recorded(&endpoint.url, recorder).await?;
+        *rpc = Some(client.clone());
+        Ok(client)
+    }
+    
+    // Verify caller with caching (matching JS verifyCaller)
+    // Asks the endpoint that delivered the log, since a slower one may not have the tx yet.
+    // Each attempt has a deadline; only Verified/Rejected answers are cached, and never an untraced fallback.
+    async fn verify_caller(&self, tx_hash: &str, endpoint: usize) -> Verification {
+        let key = tx_hash.to_string();
         
-        // Extract addresses using regex (matching JS logic exactly)
-        for cap in self.address_regex.captures_iter(data) {
-            if addresses.len() >= 10 {
-                break;
-            }
-            let addr = format!("0x{}", &cap[1]);
-            if addr != ZERO_ADDRESS {
-                addresses.push(addr);
+        // Check cache first (matching JS logic)
+        {
+            let mut cache = self.caller_cache.lock().await;
+            if let Some(initiator) = cache.get(&key) {
+                return self.classify_caller(initiator.clone());
             }
         }
         
-        if addresses.len() < 2 {
-            return None;
+        // Check rejected callers (matching JS logic)
+        {
+            let mut rejected = self.rejected_callers.lock().await;
+            if let Some(initiator) = rejected.get(&key) {
+                return Verification::Rejected(initiator.clone());
+            }
         }
         
-        // Token is at addresses[1] (matching JS exactly)
-        let token = addresses[1].clone();
+        let limits = &self.options.verification;
+        let mut reason = String::new();
         
-        // Check exact addresses first (matching JS logic)
-        for addr in &addresses {
-            if addr.to_lowercase() == WANTED.to_lowercase() {
-                return Some(TokenResult {
-                    token,
-                    confidence: Confidence::Wanted,
-                });
+        for attempt in 0..=limits.retries {
+            if attempt > 0 {
+                tokio::time::sleep(limits.retry_delay).await;
             }
-            if addr.to_lowercase() == UNWANTED.to_lowercase() {
-                return Some(TokenResult {
-                    token,
-                    confidence: Confidence::Unwanted,
-                });
+            
+            let lookup = async {
+                match limits.mode {
+                    VerifyMode::Sender => Ok(self.fetch_sender(tx_hash, endpoint).await?.map(|address| Initiator {
+                        address,
+                        path: InitiatorPath::TxSender,
+                    })),
+                    VerifyMode::Trace => self.trace_initiator(tx_hash, endpoint).await,
+                }
+            };
+            
+            match tokio::time::timeout(limits.timeout, lookup).await {
+                Ok(Ok(Some(initiator))) => {
+                    // An untraced receipt sender is a guess; a later delivery may get a real trace
+                    if matches!(initiator.path, InitiatorPath::ReceiptSender { .. }) {
+                        return self.classify_caller(initiator);
+                    }
+                    
+                    // Cache the result (matching JS logic)
+                    self.caller_cache.lock().await.insert(key.clone(), initiator.clone());
+                    
+                    let verification = self.classify_caller(initiator);
+                    
+                    // Cache rejection if not wanted (matching JS logic)
+                    if let Verification::Rejected(initiator) = &verification {
+                        self.rejected_callers.lock().await.insert(key, initiator.clone());
+                    }
+                    
+                    return verification;
+                }
+                // The node delivered the log, so the tx exists - it just isn't indexed there yet
+                Ok(Ok(None)) => reason = "transaction not found".to_string(),
+                Ok(Err(e)) => reason = e.to_string(),
+                Err(_) => reason = format!("timed out after {:?}", limits.timeout),
             }
         }
         
-        // Pattern matching fallback (matching JS logic)
-        let data_lower = data.to_lowercase();
-        if data_lower.contains(&self.unwanted_hex) {
-            return Some(TokenResult {
-                token,
-                confidence: Confidence::Unwanted,
-            });
-        }
-        if data_lower.contains(&self.wanted_hex) {
-            return Some(TokenResult {
-                token,
-                confidence: Confidence::Wanted,
-            });
+        Verification::Unknown(format!("{} ({} attempts)", reason, limits.retries + 1))
+    }
+    
+    // Get transaction over the shared socket (matching JS getTransaction)
+    async fn fetch_sender(&self, tx_hash: &str, endpoint: usize) -> Result<Option<Address>> {
+        let result = self.rpc(endpoint).await?
+            .request("eth_getTransactionByHash", serde_json::json!([tx_hash]))
+            .await?;
+        if result.is_null() {
+            return Ok(None);
         }
         
-        Some(TokenResult {
-            token,
-            confidence: Confidence::Verify,
-        })
+        result["from"]
+            .as_str()
+            .and_then(|f| f.parse::<Address>().ok())
+            .map(Some)
+            .ok_or_else(|| anyhow!("Transaction has no valid sender: {}", result))
     }
     
-    // Verify caller with caching (matching JS verifyCaller)
-    async fn verify_caller(&self, tx_hash: &str) -> Result<bool> {
-        // Check cache first (matching JS logic)
+    // Receipt first; only a tx that reached the factory through another contract needs a trace
+    async fn trace_initiator(&self, tx_hash: &str, endpoint: usize) -> Result<Option<Initiator>> {
+        let rpc = self.rpc(endpoint).await?;
+        let receipt = rpc
+            .request("eth_getTransactionReceipt", serde_json::json!([tx_hash]))
+            .await?;
+        if receipt.is_null() {
+            return Ok(None);
+        }
+        
+        let sender = receipt["from"]
+            .as_str()
+            .and_then(|f| f.parse::<Address>().ok())
+            .ok_or_else(|| anyhow!("Receipt has no valid sender: {}", receipt))?;
+        let to = receipt["to"].as_str().and_then(|t| t.parse::<Address>().ok());
+        if to == Some(self.profile.deployer) {
+            return Ok(Some(Initiator { address: sender, path: InitiatorPath::DirectCall }));
+        }
+        
+        let trace = match rpc
+            .request("debug_traceTransaction", serde_json::json!([tx_hash, {"tracer": "callTracer"}]))
+            .await
         {
-            let cache = self.caller_cache.lock().await;
-            if let Some(caller) = cache.get(tx_hash) {
-                return Ok(caller.to_lowercase() == WANTED.to_lowercase());
+            Ok(trace) => trace,
+            // Provider without the debug namespace - a weaker answer; any other failure is inconclusive
+            Err(e) if e.downcast_ref::<RpcError>().is_some_and(RpcError::is_method_not_found) => {
+                return Ok(Some(Initiator {
+                    address: sender,
+                    path: InitiatorPath::ReceiptSender { reason: e.to_string() },
+                }));
             }
+            Err(e) => return Err(e),
+        };
+        
+        Ok(Some(match factory_call_chain(&trace, self.profile.deployer) {
+            Some(chain) => Initiator {
+                address: *chain.last().expect("chain holds at least the factory's caller"),
+                path: InitiatorPath::CallFrame { chain },
+            },
+            None => Initiator {
+                address: sender,
+                path: InitiatorPath::ReceiptSender { reason: "no call frame into the factory".to_string() },
+            },
+        }))
+    }
+    
+    fn classify_caller(&self, initiator: Initiator) -> Verification {
+        if self.profile.is_wanted(&initiator.address) {
+            Verification::Verified(initiator)
+        } else {
+            Verification::Rejected(initiator)
         }
+    }
+    
+    // Process events (matching JS processEvent) - Returns detection if found
+    async fn process_event(&self, log_data: &Value, received_at: DateTime<Utc>, endpoint: usize) -> Result<Option<DetectionEvent>> {
+        let tx_hash = log_data["transactionHash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing transaction hash"))?;
         
-        // Check rejected callers (matching JS logic)
+        // Log reorged out - retract instead of treating it as a fresh launch
+        if log_data["removed"].as_bool() == Some(true) {
+            self.processed_txs.lock().await.remove(&tx_hash.to_string());
+            return Ok(self.retract(log_data).await?.map(DetectionEvent::Retracted));
+        }
+        
+        // Check if already processed (matching JS logic)
         {
-            let rejected = self.rejected_callers.lock().await;
-            if rejected.contains(tx_hash) {
-                return Ok(false);
+            // Bounded LRU/TTL: old entries age out one by one, so a replay is never let through by a wholesale clear
+            let mut processed = self.processed_txs.lock().await;
+            if !processed.insert(tx_hash.to_string(), ()) {
+                return Ok(None);
+            }
+        }
+        
+        // An inconclusive verification must not block a redelivery of the same log
+        let detection = match self.evaluate(log_data, received_at, endpoint).await {
+            Ok(detection) => detection,
+            Err(e) => {
+                self.processed_txs.lock().await.remove(&tx_hash.to_string());
+                return Err(e);
             }
+        };
+        if let Some(detection) = &detection {
+            self.reorgs.lock().await.track(detection);
         }
+        Ok(detection.map(DetectionEvent::Detected))
+    }
+    
+    // Match a removed log against what we emitted; the tx may be re-mined and re-evaluated
+    async fn retract(&self, log_data: &Value) -> Result<Option<Retraction>> {
+        let position = LogPosition::from_log(log_data)?;
+        let Some(detection) = self.reorgs.lock().await.retract(&position) else {
+            info!("↩️ Removed log for tx {:?} (never detected) - ignoring", position.tx_hash);
+            return Ok(None);
+        };
         
-        // Get transaction via WebSocket (matching JS getTransaction)
-        let (ws_stream, _) = connect_async(&self.wss_url).await?;
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        warn!("↩️ RETRACTED: {:?} - launch log removed by reorg (block {:?})", detection.token, position.block_hash);
+        Ok(Some(Retraction {
+            detection,
+            retracted_at: Utc::now(),
+        }))
+    }
+    
+    // Classify one launch log and verify its caller if needed
+    async fn evaluate(&self, log_data: &Value, received_at: DateTime<Utc>, endpoint: usize) -> Result<Option<Detection>> {
+        let position = LogPosition::from_log(log_data)?;
+        let tx_hash = format!("{:?}", position.tx_hash);
         
-        let request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "method": "eth_getTransactionByHash",
-            "params": [tx_hash],
-            "id": 1
-        });
+        let Ok(event) = LaunchEvent::from_log(log_data) else {
+            return Ok(None);
+        };
+        let token = event.token;
+        // Only a strategy that reads metadata waits for it; a pending token has none
+        let wants_metadata = self.strategy.wants_metadata();
+        let mut metadata = None;
+        if wants_metadata {
+            metadata = self.token_metadata(token, None, position.block_number, endpoint).await;
+        }
+        let decision = self.strategy.decide(&Candidate::log(&event, None).with_metadata(metadata.as_ref()));
+        let classified_at = Utc::now();
         
-        ws_sender.send(Message::Text(request.to_string())).await?;
-        
-        while let Some(msg) = ws_receiver.next().await {
-            match msg? {
-                Message::Text(text) => {
-                    if let Ok(json) = serde_json::from_str::<Value>(&text) {
-                        if let Some(result) = json.get("result") {
-                            if result.is_null() {
-                                // Transaction not found, reject
-                                let mut rejected = self.rejected_callers.lock().await;
-                                rejected.insert(tx_hash.to_string());
-                                return Ok(false);
-                            }
-                            
-                            if let Some(from_addr) = result["from"].as_str() {
-                                // Cache the result (matching JS logic)
-                                {
-                                    let mut cache = self.caller_cache.lock().await;
-                                    cache.insert(tx_hash.to_string(), from_addr.to_string());
-                                }
-                                
-                                let is_wanted = from_addr.to_lowercase() == WANTED.to_lowercase();
-                                
-                                // Cache rejection if not wanted (matching JS logic)
-                                if !is_wanted {
-                                    let mut rejected = self.rejected_callers.lock().await;
-                                    rejected.insert(tx_hash.to_string());
-                                }
-                                
-                                return Ok(is_wanted);
-                            }
-                        }
+        // Decided from the event alone, or once the caller is known (matching JS logic)
+        let (matched_address, caller, caller_path, rule) = match decision {
+            Decision::Accept { rule, by } => {
+                info!("🚀 DETECTED: {:?}", token);
+                (by, None, None, rule)
+            }
+            Decision::Reject(reason) => {
+                info!("❌ UNWANTED: {:?} ({}) - continuing monitoring...", token, reason);
+                return Ok(None);
+            }
+            Decision::Abstain => {
+                info!("⏭️ IGNORED: {:?} (no detector matched) - continuing monitoring...", token);
+                return Ok(None);
+            }
+            Decision::NeedsCaller if !self.options.use_tx_verification => {
+                info!("🚀 DETECTED: {:?} (trust mode)", token);
+                (None, None, None, MatchRule::TrustMode)
+            }
+            Decision::NeedsCaller => {
+                // Verified/Rejected reflect the wanted list; the strategy has the final say
+                let initiator = match self.verify_caller(&tx_hash, endpoint).await {
+                    Verification::Verified(initiator) | Verification::Rejected(initiator) => initiator,
+                    Verification::Unknown(reason) => {
+                        info!("❓ VERIFY UNKNOWN: {:?} ({}) - continuing monitoring...", token, reason);
+                        return Err(anyhow!("Caller verification inconclusive for {}: {}", tx_hash, reason));
+                    }
+                };
+                // Again with the caller, for its token balance
+                if wants_metadata {
+                    metadata = self.token_metadata(token, Some(initiator.address), position.block_number, endpoint).await.or(metadata);
+                }
+                match self.strategy.decide(&Candidate::log(&event, Some(initiator.address)).with_metadata(metadata.as_ref())) {
+                    Decision::Accept { rule, by } => {
+                        info!("🚀 DETECTED: {:?} (verified caller {:?} via {})", token, initiator.address, initiator.path);
+                        (by, Some(initiator.address), Some(initiator.path), rule)
+                    }
+                    decision => {
+                        info!("❌ REJECTED: {:?} (caller {:?} via {}, {}) - continuing monitoring...", token, initiator.address, initiator.path, decision);
+                        return Ok(None);
                     }
                 }
-                Message::Close(_) => break,
-                _ => {}
             }
-        }
+        };
         
-        // Network error, reject (matching JS catch block)
-        let mut rejected = self.rejected_callers.lock().await;
-        rejected.insert(tx_hash.to_string());
-        Ok(false)
+        Ok(Some(Detection {
+            chain_id: self.profile.chain_id,
+            token,
+            event,
+            position,
+            matched_address,
+            caller,
+            caller_path,
+            confidence: rule.into(),
+            rule,
+            received_at,
+            classified_at,
+            decided_at: Utc::now(),
+            metadata,
+        }))
     }
     
-    // Process events (matching JS processEvent) - Returns token if found
-    async fn process_event<F, Fut>(&self, log_data: &Value, callback: Option<F>) -> Result<Option<String>>
-    where
-        F: FnOnce(String) -> Fut + Send + 'static,
-        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
-    {
-        let tx_hash = log_data["transactionHash"]
+    // Name, symbol, supply, owner and creator balance in one Multicall3 eth_call; failures only cost the metadata
+    async fn token_metadata(&self, token: Address, creator: Option<Address>, block: Option<u64>, endpoint: usize) -> Option<TokenMetadata> {
+        let fetch = async {
+            let rpc = self.rpc(endpoint).await?;
+            metadata::fetch_metadata(&rpc, token, creator, block).await
+        };
+        
+        match tokio::time::timeout(Duration::from_millis(METADATA_TIMEOUT_MS), fetch).await {
+            Ok(Ok(metadata)) => {
+                info!("🏷️ {:?}: {}", token, metadata);
+                Some(metadata)
+            }
+            Ok(Err(e)) => {
+                warn!("⚠️ Metadata for {:?} unavailable: {}", token, e);
+                None
+            }
+            Err(_) => {
+                warn!("⚠️ Metadata for {:?} timed out after {}ms", token, METADATA_TIMEOUT_MS);
+                None
+            }
+        }
+    }
+    
+    // ENRICH_METADATA for a detection decided without it; runs once the detection is out
+    async fn enrich(&self, detection: &Detection, endpoint: usize) -> Option<Detection> {
+        if !self.options.enrich_metadata || detection.metadata.is_some() {
+            return None;
+        }
+        let metadata = self.token_metadata(detection.token, detection.caller, detection.position.block_number, endpoint).await?;
+        Some(Detection { metadata: Some(metadata), ..detection.clone() })
+    }
+    
+    // Classify a pending factory call by its sender; the mined log is still processed normally
+    async fn process_pending_tx(&self, rpc: &RpcClient, tx: &Value, received_at: DateTime<Utc>) -> Result<Option<PendingLaunch>> {
+        if tx["to"].as_str().and_then(|to| to.parse::<Address>().ok()) != Some(self.profile.deployer) {
+            return Ok(None);
+        }
+        
+        let tx_hash = tx["hash"]
             .as_str()
             .ok_or_else(|| anyhow!("Missing transaction hash"))?;
         
-        // Check if already processed (matching JS logic)
         {
-            let mut processed = self.processed_txs.lock().await;
-            if processed.contains(tx_hash) {
+            let mut pending = self.pending_txs.lock().await;
+            if !pending.insert(tx_hash.to_string(), ()) {
+                return Ok(None);
+            }
+        }
+        
+        let input = tx["input"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing transaction input"))?
+            .parse::<Bytes>()
+            .map_err(|e| anyhow!("Invalid transaction input: {}", e))?;
+        let Some(call) = LaunchCall::decode(&input) else {
+            return Ok(None);
+        };
+        
+        let creator = tx["from"]
+            .as_str()
+            .and_then(|from| from.parse::<Address>().ok())
+            .ok_or_else(|| anyhow!("Missing transaction sender"))?;
+        let rule = match self.strategy.decide(&Candidate::pending(&call, creator)) {
+            Decision::Accept { rule, .. } => rule,
+            decision => {
+                info!("❌ PENDING REJECTED: {} #{} from {:?} ({}) - continuing monitoring...", call.function, call.application_id, creator, decision);
                 return Ok(None);
             }
+        };
+        
+        let launch = PendingLaunch {
+            chain_id: self.profile.chain_id,
+            tx_hash: tx_hash.parse().map_err(|e| anyhow!("Invalid transaction hash: {}", e))?,
+            creator,
+            call,
+            predicted_token: self.predict_token(rpc).await?,
+            rule,
+            received_at,
+            decided_at: Utc::now(),
+        };
+        info!("🔮 PENDING LAUNCH: {}", launch);
+        Ok(Some(launch))
+    }
+    
+    // The token is the first clone the factory CREATEs, so it takes the factory's next nonce.
+    // Other factory calls ahead of this tx in the block shift it; the guess is never checked.
+    async fn predict_token(&self, rpc: &RpcClient) -> Result<Address> {
+        let nonce = rpc
+            .request("eth_getTransactionCount", serde_json::json!([self.profile.deployer, "pending"]))
+            .await?;
+        let nonce = nonce
+            .as_str()
+            .and_then(parse_hex_u64)
+            .ok_or_else(|| anyhow!("Unexpected eth_getTransactionCount result: {}", nonce))?;
+        Ok(ethers::utils::get_contract_address(self.profile.deployer, nonce))
+    }
+    
+    // Live detections and reorg retractions as a stream; runs until stop() is called or the stream is dropped.
+    // Every endpoint is subscribed at once and the first copy of each log wins.
+    // An endpoint whose node refuses the subscription drops out; once all have, the error is
+    // yielded and the stream ends. Dedupe state is kept across streams and ages out entry by entry.
+    pub fn detections(&self) -> impl Stream<Item = Result<DetectionEvent>> {
+        let (sender, mut receiver) = mpsc::channel(DETECTION_BUFFER);
+        let detector = self.clone();
+        
+        tokio::spawn(async move {
+            info!("🔍 Monitoring for tokens from: {:?}", detector.profile.wanted);
+            info!("❌ Will reject tokens from: {:?}", detector.profile.unwanted);
             
-            // Simple cache management (matching JS logic exactly)
-            if processed.len() >= 1000 {
-                processed.clear();
-                let mut cache = self.caller_cache.lock().await;
-                if cache.len() >= 500 {
-                    cache.clear();
+            let sessions = (0..detector.endpoints.len()).map(|endpoint| detector.supervise(endpoint, &sender));
+            let results = futures_util::future::join_all(sessions).await;
+            if results.iter().all(Result::is_err) {
+                if let Some(Err(e)) = results.into_iter().last() {
+                    let _ = sender.send(Err(e)).await;
+                }
+            }
+        });
+        
+        futures_util::stream::poll_fn(move |cx| receiver.poll_recv(cx))
+    }
+    
+    // Ask the live subscription to wind down; ends every stream from detections()
+    pub async fn stop(&self) {
+        self.stop.send_replace(true);
+    }
+    
+    fn is_stopped(&self) -> bool {
+        *self.stop.borrow()
+    }
+    
+    // Resolves once stop() has been called, even if it already was
+    async fn stopped(&self) {
+        let mut stop = self.stop.subscribe();
+        let _ = stop.wait_for(|stopped| *stopped).await;
+    }
+    
+    // Size and eviction counters of the dedupe and verification caches
+    pub async fn cache_stats(&self) -> Vec<CacheStats> {
+        vec![
+            self.processed_txs.lock().await.stats(),
+            self.pending_txs.lock().await.stats(),
+            self.caller_cache.lock().await.stats(),
+            self.rejected_callers.lock().await.stats(),
+        ]
+    }
+    
+    // Per-endpoint first-seen wins and lag behind the winner, for dropping slow providers
+    pub async fn endpoint_stats(&self) -> Vec<EndpointStats> {
+        self.race.lock().await.stats()
+    }
+    
+    // Supervises one endpoint's subscription: reconnects with backoff and backfills missed blocks
+    async fn supervise(&self, endpoint: usize, sender: &mpsc::Sender<Result<DetectionEvent>>) -> Result<()> {
+        let mut delay_ms = RECONNECT_BASE_DELAY_MS;
+        
+        loop {
+            match self.run_subscription(endpoint, sender, &mut delay_ms).await {
+                Ok(SessionEnd::Stopped) => return Ok(()),
+                Ok(SessionEnd::Rejected(error)) => {
+                    error!("❌ Endpoint {} subscription failed: {}", endpoint, error);
+                    return Err(anyhow!("Subscription error: {}", error));
                 }
-                let mut rejected = self.rejected_callers.lock().await;
-                if rejected.len() >= 100 {
-                    rejected.clear();
+                Err(e) => {
+                    error!("🔌 Endpoint {} subscription dropped: {} - reconnecting in {}ms", endpoint, e, delay_ms);
                 }
             }
             
-            processed.insert(tx_hash.to_string());
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(delay_ms)) => {}
+                _ = self.stopped() => return Ok(()),
+            }
+            if sender.is_closed() {
+                return Ok(());
+            }
+            delay_ms = (delay_ms * 2).min(RECONNECT_MAX_DELAY_MS);
         }
+    }
+    
+    // One connection lifetime: subscribe, backfill the gap, then stream events.
+    // Returns Err for anything worth reconnecting over.
+    async fn run_subscription(&self, endpoint: usize, sender: &mpsc::Sender<Result<DetectionEvent>>, delay_ms: &mut u64) -> Result<SessionEnd> {
+        let rpc = self.rpc(endpoint).await?;
         
-        let data = log_data["data"]
-            .as_str()
-            .ok_or_else(|| anyhow!("Missing log data"))?;
+        let filter = serde_json::json!({
+            "address": self.profile.deployer,
+            "topics": [self.profile.topic]
+        });
         
-        let result = match self.extract_token_and_caller(data) {
-            Some(result) => result,
-            None => return Ok(None),
+        // Subscribe to logs; a node-side rejection is not worth retrying
+        let mut subscription = match rpc.subscribe(serde_json::json!(["logs", filter])).await {
+            Ok(subscription) => subscription,
+            Err(e) if e.downcast_ref::<RpcError>().is_some() => return Ok(SessionEnd::Rejected(e.to_string())),
+            Err(e) => return Err(e),
         };
         
-        // Handle based on confidence (matching JS logic exactly)
-        match result.confidence {
-            Confidence::Wanted => {
-                info!("🚀 DETECTED: {}", result.token);
-                // Set stop flag before executing callback (matching JS)
-                {
-                    let mut should_stop = self.should_stop.lock().await;
-                    *should_stop = true;
-                }
+        info!("✅ Endpoint {} subscription established: {}", endpoint, subscription.id);
+        
+        let mut pending = if self.options.use_mempool {
+            self.subscribe_pending(&rpc).await?
+        } else {
+            None
+        };
+        
+        let head = rpc.request("eth_blockNumber", serde_json::json!([])).await?
+            .as_str()
+            .and_then(parse_hex_u64);
+        
+        // Backfill anything mined while we were disconnected, in chunks up to the head;
+        // live events queue up meanwhile. A failed backfill reconnects and tries again
+        // from the same block.
+        let last_block = *self.last_block.lock().await;
+        if let (Some(from_block), Some(head)) = (last_block, head) {
+            if from_block <= head {
+                info!("⏪ Backfilling logs from block {} to {}", from_block, head);
+                let options = ScanOptions { checkpoint: None, ..ScanOptions::from_env() };
+                let logs = scan::scan_logs(&filter, from_block, head, &options, |from, to| {
+                    let rpc = rpc.clone();
+                    let mut chunk_filter = filter.clone();
+                    chunk_filter["fromBlock"] = Value::String(format!("0x{:x}", from));
+                    chunk_filter["toBlock"] = Value::String(format!("0x{:x}", to));
+                    async move {
+                        let result = rpc.request("eth_getLogs", serde_json::json!([chunk_filter])).await?;
+                        result
+                            .as_array()
+                            .cloned()
+                            .ok_or_else(|| anyhow!("Unexpected eth_getLogs result: {}", result))
+                    }
+                }).await.map_err(|e| anyhow!("Backfill from block {} failed: {}", from_block, e))?;
                 
-                // Execute callback if provided
-                if let Some(cb) = callback {
-                    info!("🔄 Callback triggered for: {}", result.token);
-                    info!("⚡ Executing onTokenFound callback...");
-                    match cb(result.token.clone()).await {
-                        Ok(_) => {
-                            info!("✅ Callback completed successfully");
-                        }
-                        Err(e) => {
-                            error!("❌ Callback execution failed: {}", e);
-                        }
+                info!("⏪ Backfill returned {} logs", logs.len());
+                for log in &logs {
+                    if !self.handle_log(log, sender, endpoint).await {
+                        return Ok(SessionEnd::Stopped);
                     }
                 }
-                return Ok(Some(result.token));
-            }
-            Confidence::Unwanted => {
-                info!("❌ UNWANTED: {} from {} - continuing monitoring...", result.token, UNWANTED);
-            }
-            Confidence::Verify => {
-                if self.use_tx_verification {
-                    match self.verify_caller(tx_hash).await {
-                        Ok(true) => {
-                            info!("🚀 DETECTED: {}", result.token);
-                            // Set stop flag before executing callback (matching JS)
-                            {
-                                let mut should_stop = self.should_stop.lock().await;
-                                *should_stop = true;
-                            }
-                            
-                            // Execute callback if provided
-                            if let Some(cb) = callback {
-                                info!("🔄 Verification callback triggered for: {}", result.token);
-                                info!("⚡ Executing onTokenFound callback...");
-                                match cb(result.token.clone()).await {
-                                    Ok(_) => {
-                                        info!("✅ Verification callback completed successfully");
-                                    }
-                                    Err(e) => {
-                                        error!("❌ Verification callback failed: {}", e);
-                                    }
-                                }
-                            }
-                            return Ok(Some(result.token));
-                        }
-                        Ok(false) => {
-                            info!("❌ REJECTED: {} (wrong caller) - continuing monitoring...", result.token);
-                        }
-                        Err(_) => {
-                            info!("❌ VERIFY ERROR: {} (network issue) - continuing monitoring...", result.token);
-                        }
-                    }
-                } else {
-                    info!("🚀 DETECTED: {}", result.token);
-                    // Set stop flag before executing callback (matching JS)
-                    {
-                        let mut should_stop = self.should_stop.lock().await;
-                        *should_stop = true;
-                    }
-                    
-                    // Execute callback if provided
-                    if let Some(cb) = callback {
-                        info!("🔄 Trust mode callback triggered for: {}", result.token);
-                        info!("⚡ Executing onTokenFound callback...");
-                        match cb(result.token.clone()).await {
-                            Ok(_) => {
-                                info!("✅ Trust mode callback completed successfully");
-                            }
-                            Err(e) => {
-                                error!("❌ Trust mode callback failed: {}", e);
-                            }
+            }
+        }
+        
+        // Caught up: record the head so the next reconnect knows where to resume
+        if let Some(head) = head {
+            self.record_block(head).await;
+        }
+        *delay_ms = RECONNECT_BASE_DELAY_MS;
+        
+        // Handle subscription events (matching JS format)
+        loop {
+            let keep_going = tokio::select! {
+                result = subscription.next() => match result {
+                    Some(result) => {
+                        if self.first_to_arrive(endpoint, &result).await {
+                            info!("🔍 Processing subscription event");
+                            self.handle_log(&result, sender, endpoint).await
+                        } else {
+                            !self.is_stopped()
                         }
                     }
-                    return Ok(Some(result.token));
+                    None => break,
+                },
+                Some(tx) = next_pending(&mut pending) => self.handle_pending_tx(&rpc, &tx, sender).await,
+                // Quiet chains deliver nothing to check the flag on
+                _ = self.stopped() => false,
+            };
+            
+            if !keep_going {
+                let _ = rpc.unsubscribe(&subscription).await;
+                if let Some(pending) = &pending {
+                    let _ = rpc.unsubscribe(pending).await;
                 }
+                return Ok(SessionEnd::Stopped);
             }
         }
         
+        Err(anyhow!("WebSocket connection closed"))
+    }
+    
+    // Pending transactions as full objects where supported, otherwise hashes.
+    // A node that refuses both just runs without mempool detection.
+    async fn subscribe_pending(&self, rpc: &RpcClient) -> Result<Option<Subscription>> {
+        for params in [serde_json::json!(["newPendingTransactions", true]), serde_json::json!(["newPendingTransactions"])] {
+            match rpc.subscribe(params).await {
+                Ok(subscription) => {
+                    info!("✅ Pending transaction subscription established: {}", subscription.id);
+                    return Ok(Some(subscription));
+                }
+                Err(e) if e.downcast_ref::<RpcError>().is_some() => {
+                    error!("❌ Pending transaction subscription refused: {}", e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
         Ok(None)
     }
     
-    // Main function - Live token detection (matching JS getTokenAddress)
-    pub async fn get_token_address<F, Fut>(&self, on_token_found: Option<F>) -> Result<String>
-    where
-        F: FnOnce(String) -> Fut + Send + 'static,
-        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
-    {
-        // Reset state (matching JS logic)
-        {
-            let mut should_stop = self.should_stop.lock().await;
-            *should_stop = false;
+    // Resolve one pending-tx notification and forward any provisional detection.
+    // Returns false once the consumer is gone or a stop was requested.
+    async fn handle_pending_tx(&self, rpc: &RpcClient, notification: &Value, sender: &mpsc::Sender<Result<DetectionEvent>>) -> bool {
+        let received_at = Utc::now();
+        if self.is_stopped() {
+            return false;
         }
-        {
-            let mut processed = self.processed_txs.lock().await;
-            processed.clear();
-        }
-        
-        info!("🔍 Monitoring for tokens from: {}", WANTED);
-        info!("❌ Will reject tokens from: {}", UNWANTED);
-        
-        // Connect to WebSocket
-        let (ws_stream, _) = connect_async(&self.wss_url).await
-            .map_err(|e| anyhow!("Failed to connect to WebSocket: {}", e))?;
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-        
-        // Subscribe to logs
-        let subscription = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "eth_subscribe",
-            "params": [
-                "logs",
-                {
-                    "address": DEPLOYER,
-                    "topics": [TARGET_TOPIC]
-                }
-            ]
-        });
         
-        ws_sender.send(Message::Text(subscription.to_string())).await
-            .map_err(|e| anyhow!("Failed to send subscription: {}", e))?;
+        if let Some(launch) = self.resolve_pending_tx(rpc, notification, received_at).await {
+            return sender.send(Ok(DetectionEvent::Provisional(launch))).await.is_ok();
+        }
+        true
+    }
+    
+    async fn resolve_pending_tx(&self, rpc: &RpcClient, notification: &Value, received_at: DateTime<Utc>) -> Option<PendingLaunch> {
+        // Hash-only notifications need a lookup
+        let fetched;
+        let tx = match notification.as_str() {
+            Some(hash) => match rpc.request("eth_getTransactionByHash", serde_json::json!([hash])).await {
+                Ok(tx) if !tx.is_null() => {
+                    fetched = tx;
+                    &fetched
+                }
+                _ => return None,
+            },
+            None => notification,
+        };
         
-        info!("📤 Sent WebSocket subscription request");
+        self.process_pending_tx(rpc, tx, received_at).await.ok().flatten()
+    }
+    
+    // Dedupe live logs across endpoints by (block hash, tx hash, log index); removals race separately.
+    // The block hash keeps a tx re-mined after a reorg from looking like a copy of the orphaned log.
+    async fn first_to_arrive(&self, endpoint: usize, log: &Value) -> bool {
+        let arrived_at = Instant::now();
+        let key = format!("{}:{}:{}:{}",
+            log["blockHash"].as_str().unwrap_or_default(),
+            log["transactionHash"].as_str().unwrap_or_default(),
+            log["logIndex"].as_str().unwrap_or_default(),
+            log["removed"].as_bool().unwrap_or(false));
+        self.race.lock().await.observe(endpoint, &key, arrived_at)
+    }
+    
+    // Track progress, run one log through process_event and forward any detection.
+    // Returns false once the consumer is gone or a stop was requested.
+    async fn handle_log(&self, log: &Value, sender: &mpsc::Sender<Result<DetectionEvent>>, endpoint: usize) -> bool {
+        let received_at = Utc::now();
+        if self.is_stopped() {
+            return false;
+        }
         
-        let mut callback_option = on_token_found;
-        let mut subscription_confirmed = false;
+        // Removed logs point at an orphaned block, which must not move the backfill start
+        if log["removed"].as_bool() != Some(true) {
+            if let Some(block) = log["blockNumber"].as_str().and_then(parse_hex_u64) {
+                self.record_block(block).await;
+            }
+        }
         
-        // Process incoming messages
-        while let Some(msg) = ws_receiver.next().await {
-            // Check if we should stop
-            {
-                let should_stop = self.should_stop.lock().await;
-                if *should_stop {
-                    break;
-                }
+        if let Ok(Some(event)) = self.process_event(log, received_at, endpoint).await {
+            let detected = match &event {
+                DetectionEvent::Detected(detection) if self.options.enrich_metadata => Some(detection.clone()),
+                _ => None,
+            };
+            if sender.send(Ok(event)).await.is_err() {
+                return false;
             }
-            
-            match msg.map_err(|e| anyhow!("WebSocket error: {}", e))? {
-                Message::Text(text) => {
-                    info!("📥 Received WebSocket message: {}", text);
-                    
-                    if let Ok(json) = serde_json::from_str::<Value>(&text) {
-                        // Handle subscription confirmation
-                        if json.get("id").is_some() && json.get("result").is_some() {
-                            if let Some(sub_id) = json["result"].as_str() {
-                                subscription_confirmed = true;
-                                info!("✅ WebSocket subscription established: {}", sub_id);
-                                continue;
-                            }
-                        }
-                        
-                        // Handle subscription errors
-                        if let Some(error) = json.get("error") {
-                            error!("❌ Subscription failed: {}", error);
-                            return Err(anyhow!("Subscription error: {}", error));
-                        }
-                        
-                        // Only process events after subscription is confirmed
-                        if !subscription_confirmed {
-                            info!("⏳ Waiting for subscription confirmation...");
-                            continue;
-                        }
-                        
-                        // Handle subscription events (matching JS format)
-                        if json.get("method").and_then(|m| m.as_str()) == Some("eth_subscription") {
-                            if let Some(params) = json.get("params") {
-                                if let Some(result) = params.get("result") {
-                                    info!("🔍 Processing subscription event");
-                                    // Process event and get token immediately if found
-                                    if let Some(callback) = callback_option.take() {
-                                        if let Ok(Some(token)) = self.process_event(result, Some(callback)).await {
-                                            info!("🎯 Returning detected token immediately: {}", token);
-                                            return Ok(token);
-                                        }
-                                    } else {
-                                        // No callback, just return first detected token
-                                        if let Ok(Some(token)) = self.process_event(result, None::<fn(String) -> futures_util::future::Ready<Result<()>>>).await {
-                                            info!("🎯 Returning detected token immediately: {}", token);
-                                            return Ok(token);
-                                        }
-                                    }
-                                }
-                            }
+            // Metadata follows as its own event so it never delays the swap
+            if let Some(detection) = detected {
+                let (detector, sender) = (self.clone(), sender.clone());
+                tokio::spawn(async move {
+                    if let Some(enriched) = detector.enrich(&detection, endpoint).await {
+                        let _ = sender.send(Ok(DetectionEvent::Enriched(enriched))).await;
+                    }
+                });
+            }
+        }
+        true
+    }
+    
+    // Highest block seen, used as the backfill starting point after a reconnect
+    async fn record_block(&self, block: u64) {
+        let mut last_block = self.last_block.lock().await;
+        if last_block.is_none_or(|last| block > last) {
+            *last_block = Some(block);
+        }
+    }
+
+    // ============================================================================
+    // REPLAY - captured traffic through the live classification path
+    // ============================================================================
+
+    // Feed a capture's notifications, in arrival order and with their capture times, through
+    // process_event (and process_pending_tx with USE_MEMPOOL). verify_caller and every other
+    // lookup is answered from the same capture, so a run is deterministic and offline.
+    pub async fn replay(&self, recording: &Recording) -> Result<Vec<DetectionEvent>> {
+        let rpc = self.rpc(PRIMARY_ENDPOINT).await?;
+        let mut events = Vec::new();
+        
+        for notification in recording.notifications() {
+            match notification.kind.as_str() {
+                "logs" => {
+                    // Copies from other raced endpoints are dropped by process_event's dedupe
+                    match self.process_event(&notification.result, notification.at, PRIMARY_ENDPOINT).await {
+                        Ok(Some(DetectionEvent::Detected(detection))) => {
+                            let enriched = self.enrich(&detection, PRIMARY_ENDPOINT).await;
+                            events.push(DetectionEvent::Detected(detection));
+                            events.extend(enriched.map(DetectionEvent::Enriched));
                         }
-                    } else {
-                        error!("❌ Failed to parse WebSocket message as JSON: {}", text);
+                        Ok(Some(event)) => events.push(event),
+                        Ok(None) => {}
+                        Err(e) => warn!("⚠️ Replayed log failed: {}", e),
                     }
                 }
-                Message::Close(_) => {
-                    info!("🔌 WebSocket connection closed");
-                    return Err(anyhow!("WebSocket connection closed"));
+                "newPendingTransactions" if self.options.use_mempool => {
+                    if let Some(launch) = self.resolve_pending_tx(&rpc, &notification.result, notification.at).await {
+                        events.push(DetectionEvent::Provisional(launch));
+                    }
                 }
                 _ => {}
             }
         }
         
-        Ok("No token detected".to_string())
+        info!("⏯️ Replayed {} notifications: {} events", recording.notifications().len(), events.len());
+        Ok(events)
+    }
+
+    // ============================================================================
+    // HISTORICAL TESTING - same classification as live detection
+    // ============================================================================
+
+    // Test token detection for a specific block
+    pub async fn test_block(&self, block_number: u64) -> Result<Vec<Detection>> {
+        info!("🧪 Testing block {} for token deployments...", block_number);
+        
+        let mut detected_tokens = Vec::new();
+        
+        // Get logs from specific block using eth_getLogs
+        let block_hex = format!("0x{:x}", block_number);
+        let result = self.rpc(PRIMARY_ENDPOINT).await?.request("eth_getLogs", serde_json::json!([{
+            "address": self.profile.deployer,
+            "topics": [self.profile.topic],
+            "fromBlock": block_hex,
+            "toBlock": block_hex
+        }])).await?;
+        
+        if let Some(logs) = result.as_array() {
+            info!("📊 Found {} logs in block {}", logs.len(), block_number);
+            
+            for log in logs {
+                // Use same logic as live detection for consistency
+                if let Ok(Some(detection)) = self.evaluate(log, Utc::now(), PRIMARY_ENDPOINT).await {
+                    let detection = self.enrich(&detection, PRIMARY_ENDPOINT).await.unwrap_or(detection);
+                    info!("✅ Token detected: {}", detection);
+                    detected_tokens.push(detection);
+                }
+            }
+        }
+        
+        if detected_tokens.is_empty() {
+            info!("🔍 No matching tokens found in block {}", block_number);
+        } else {
+            info!("🎯 Detected {} matching tokens in block {}", detected_tokens.len(), block_number);
+        }
+        
+        Ok(detected_tokens)
+    }
+
+    // Test token detection for a range of blocks.
+    // Scanned in chunks (SCAN_CHUNK_SIZE) with SCAN_CONCURRENCY requests in flight;
+    // set SCAN_CHECKPOINT to make long scans resumable.
+    pub async fn test_block_range(&self, from_block: u64, to_block: u64) -> Result<Vec<Detection>> {
+        info!("🧪 Testing block range {} to {} for token deployments...", from_block, to_block);
+        
+        let mut all_detected_tokens = Vec::new();
+        
+        let options = ScanOptions::from_env();
+        let rpc = self.rpc(PRIMARY_ENDPOINT).await?;
+        let filter = serde_json::json!({
+            "address": self.profile.deployer,
+            "topics": [self.profile.topic]
+        });
+        
+        // Get logs from block range using eth_getLogs, one chunk per request
+        let logs = scan::scan_logs(&filter, from_block, to_block, &options, |from, to| {
+            let rpc = rpc.clone();
+            let mut chunk_filter = filter.clone();
+            chunk_filter["fromBlock"] = Value::String(format!("0x{:x}", from));
+            chunk_filter["toBlock"] = Value::String(format!("0x{:x}", to));
+            async move {
+                let result = rpc.request("eth_getLogs", serde_json::json!([chunk_filter])).await?;
+                result
+                    .as_array()
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Unexpected eth_getLogs result: {}", result))
+            }
+        }).await?;
+        
+        info!("📊 Found {} logs in block range {} to {}", logs.len(), from_block, to_block);
+        
+        for log in &logs {
+            // Use same logic as live detection for consistency
+            if let Ok(Some(detection)) = self.evaluate(log, Utc::now(), PRIMARY_ENDPOINT).await {
+                let detection = self.enrich(&detection, PRIMARY_ENDPOINT).await.unwrap_or(detection);
+                info!("✅ Token detected: {}", detection);
+                all_detected_tokens.push(detection);
+            }
+        }
+        
+        if all_detected_tokens.is_empty() {
+            info!("🔍 No matching tokens found in block range {} to {}", from_block, to_block);
+        } else {
+            info!("🎯 Detected {} matching tokens in block range {} to {}", 
+                  all_detected_tokens.len(), from_block, to_block);
+        }
+        
+        Ok(all_detected_tokens)
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
-    
-    // Load environment variables (matching JS require('dotenv').config())
-    dotenv::dotenv().ok();
-    
-    info!("🤖 Rust Sniper Bot - Starting...");
-    
-    // Create detector (matching JS global state initialization)
-    let detector = TokenDetector::new()?;
+// Callers from the root frame down to the first frame that calls `factory` (callTracer layout).
+// Delegatecalls keep the caller's address, so repeats are collapsed.
+fn factory_call_chain(frame: &Value, factory: Address) -> Option<Vec<Address>> {
+    let from = frame["from"].as_str()?.parse::<Address>().ok()?;
+    if frame["to"].as_str().and_then(|t| t.parse::<Address>().ok()) == Some(factory) {
+        return Some(vec![from]);
+    }
     
-    // CLI - Live detection only (matching JS if (require.main === module))
-    match detector.get_token_address(None::<fn(String) -> futures_util::future::Ready<Result<()>>>).await {
-        Ok(result) => {
-            println!("{}", result);
-            Ok(())
+    frame["calls"].as_array()?.iter().find_map(|call| {
+        let mut chain = factory_call_chain(call, factory)?;
+        if chain[0] != from {
+            chain.insert(0, from);
+        }
+        Some(chain)
+    })
+}
+
+// Next pending-tx notification, or never when mempool mode is off
+async fn next_pending(pending: &mut Option<Subscription>) -> Option<Value> {
+    match pending {
+        Some(subscription) => subscription.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::detection::Confidence;
+    use crate::launch_event::ExecuteApplicationCall;
+    use crate::mock_node::{launch_log, MockNode, Reply};
+    use ethers::abi::AbiEncode;
+    use futures_util::StreamExt;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn pending_tx(hash: u8, from: Address, to: Address, input: Vec<u8>) -> Value {
+        serde_json::json!({
+            "hash": format!("{:?}", ethers::types::H256::repeat_byte(hash)),
+            "from": from,
+            "to": to,
+            "input": Bytes::from(input)
+        })
+    }
+
+    fn shipped_profile() -> Profile {
+        Config::from_file(Path::new("config.toml"))
+            .and_then(|config| config.profile(&config.default_profile))
+            .unwrap()
+    }
+
+    // Script `method` by the tx hash in its first param; None leaves the request hanging
+    fn on_tx<F>(node: &MockNode, method: &str, lookup: F)
+    where
+        F: Fn(&str) -> Option<Value> + Send + 'static,
+    {
+        node.on(method, move |params| match lookup(params[0].as_str().unwrap_or_default()) {
+            Some(result) => Reply::Result(result),
+            None => Reply::Hang,
+        });
+    }
+
+    #[tokio::test]
+    async fn test_verify_caller_is_tri_state_and_caches_only_definitive_answers() {
+        let profile = shipped_profile();
+        let (wanted, unwanted) = (profile.wanted[0], profile.unwanted[0]);
+        let lookups = Arc::new(AtomicUsize::new(0));
+
+        let node = MockNode::start().await;
+        {
+            let lookups = lookups.clone();
+            on_tx(&node, "eth_getTransactionByHash", move |hash| {
+                let n = lookups.fetch_add(1, Ordering::SeqCst);
+                match hash {
+                    // Not indexed on the first lookup
+                    "0x01" if n == 0 => Some(Value::Null),
+                    "0x01" => Some(serde_json::json!({"from": wanted})),
+                    "0x02" => Some(serde_json::json!({"from": unwanted})),
+                    _ => None,
+                }
+            });
+        }
+
+        let mut options = DetectorOptions::default();
+        options.verification.timeout = Duration::from_millis(100);
+        options.verification.retry_delay = Duration::from_millis(1);
+        let detector = TokenDetector::with_options(profile, vec![node.url()], options).unwrap();
+
+        let sender = |address| Initiator { address, path: InitiatorPath::TxSender };
+        assert_eq!(detector.verify_caller("0x01", 0).await, Verification::Verified(sender(wanted)));
+        assert_eq!(detector.verify_caller("0x02", 0).await, Verification::Rejected(sender(unwanted)));
+        assert!(matches!(detector.verify_caller("0x03", 0).await, Verification::Unknown(_)));
+
+        // Definitive answers come from cache; the unknown one is asked again
+        let before = lookups.load(Ordering::SeqCst);
+        assert_eq!(detector.verify_caller("0x01", 0).await, Verification::Verified(sender(wanted)));
+        assert_eq!(detector.verify_caller("0x02", 0).await, Verification::Rejected(sender(unwanted)));
+        assert_eq!(lookups.load(Ordering::SeqCst), before);
+        assert!(matches!(detector.verify_caller("0x03", 0).await, Verification::Unknown(_)));
+        assert_eq!(lookups.load(Ordering::SeqCst), before + 3);
+    }
+
+    // Records the symbol and caller of every candidate; accepts once the caller is known
+    type Seen = Arc<std::sync::Mutex<Vec<(Option<String>, Option<Address>)>>>;
+    struct SymbolWatcher(Seen, bool);
+
+    impl LaunchDetector for SymbolWatcher {
+        fn decide(&self, candidate: &Candidate) -> Decision {
+            let symbol = candidate.metadata.and_then(|m| m.symbol.clone());
+            self.0.lock().unwrap().push((symbol, candidate.caller));
+            match candidate.caller {
+                Some(caller) => Decision::Accept { rule: MatchRule::VerifiedCaller, by: Some(caller) },
+                None => Decision::NeedsCaller,
+            }
+        }
+
+        fn wants_metadata(&self) -> bool {
+            self.1
+        }
+    }
+
+    // Node answering Multicall3 with only symbol() = "AGT"
+    async fn symbol_node(creator: Address) -> MockNode {
+        use crate::metadata::{multicall3_address, Aggregate3Call, Aggregate3Return, SymbolReturn};
+        use ethers::contract::EthCall;
+
+        let node = MockNode::start().await;
+        on_tx(&node, "eth_getTransactionByHash", move |_| Some(serde_json::json!({"from": creator})));
+        let output = Aggregate3Return { return_data: vec![
+            (false, Bytes::new()),
+            (true, SymbolReturn("AGT".to_string()).encode().into()),
+        ] };
+        node.on_call(multicall3_address(), Aggregate3Call::selector(), output.encode());
+        node
+    }
+
+    // Number of Multicall3 sub-calls in each eth_call
+    fn aggregate_sizes(node: &MockNode) -> Vec<usize> {
+        use crate::metadata::Aggregate3Call;
+        use ethers::abi::AbiDecode;
+
+        node.requests("eth_call").iter()
+            .map(|params| {
+                let data: Bytes = serde_json::from_value(params[0]["data"].clone()).unwrap();
+                Aggregate3Call::decode(&data).unwrap().calls.len()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_strategy_that_wants_metadata_sees_it_before_and_after_verification() {
+        let profile = shipped_profile();
+        let creator = Address::repeat_byte(0x77);
+        let node = symbol_node(creator).await;
+
+        let mut detector = TokenDetector::with_options(profile.clone(), vec![node.url()], DetectorOptions::default()).unwrap();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        detector.strategy = Arc::new(SymbolWatcher(seen.clone(), true));
+
+        let log = launch_log(&profile, 0x01, Address::repeat_byte(0xaa), Address::repeat_byte(0x99));
+        let detection = detector.evaluate(&log, Utc::now(), PRIMARY_ENDPOINT).await.unwrap().unwrap();
+
+        let agt = Some("AGT".to_string());
+        assert_eq!(*seen.lock().unwrap(), vec![(agt.clone(), None), (agt.clone(), Some(creator))]);
+        assert_eq!(detection.metadata.and_then(|m| m.symbol), agt);
+        // The second read adds balanceOf(creator)
+        assert_eq!(aggregate_sizes(&node), vec![5, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_enriched_metadata_follows_the_detection() {
+        let profile = shipped_profile();
+        let creator = Address::repeat_byte(0x77);
+        let node = symbol_node(creator).await;
+
+        let options = DetectorOptions { enrich_metadata: true, ..Default::default() };
+        let mut detector = TokenDetector::with_options(profile.clone(), vec![node.url()], options).unwrap();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        detector.strategy = Arc::new(SymbolWatcher(seen.clone(), false));
+        let mut detections = std::pin::pin!(detector.detections());
+        let wait = Duration::from_secs(5);
+
+        node.push_log(launch_log(&profile, 0x01, Address::repeat_byte(0xaa), Address::repeat_byte(0x99)));
+        let event = tokio::time::timeout(wait, detections.next()).await.unwrap();
+        let Some(Ok(DetectionEvent::Detected(detection))) = event else {
+            panic!("expected a detection, got {:?}", event);
+        };
+        // Decided and emitted without a metadata round trip
+        assert_eq!(*seen.lock().unwrap(), vec![(None, None), (None, Some(creator))]);
+        assert!(detection.metadata.is_none());
+
+        let event = tokio::time::timeout(wait, detections.next()).await.unwrap();
+        let Some(Ok(DetectionEvent::Enriched(enriched))) = event else {
+            panic!("expected the enriched detection, got {:?}", event);
+        };
+        assert_eq!(enriched.position.tx_hash, detection.position.tx_hash);
+        assert_eq!(enriched.metadata.and_then(|m| m.symbol), Some("AGT".to_string()));
+        assert_eq!(aggregate_sizes(&node), vec![6]);
+        detector.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_trace_mode_finds_the_account_that_called_the_factory() {
+        let profile = shipped_profile();
+        let (factory, wanted, unwanted) = (profile.deployer, profile.wanted[0], profile.unwanted[0]);
+        let (bundler, entry_point) = (Address::repeat_byte(0xb0), Address::repeat_byte(0xe0));
+
+        let node = MockNode::start().await;
+        on_tx(&node, "eth_getTransactionReceipt", move |hash| match hash {
+            // Bundled user operation: bundler -> entry point -> wanted smart wallet -> factory
+            "0x01" => Some(serde_json::json!({"from": bundler, "to": entry_point})),
+            // Direct call from an unwanted account; never traced
+            "0x02" => Some(serde_json::json!({"from": unwanted, "to": factory})),
+            // Relayed: the trace never reaches the factory (0x03) or hangs (0x04)
+            "0x03" | "0x04" => Some(serde_json::json!({"from": wanted, "to": entry_point})),
+            _ => None,
+        });
+        on_tx(&node, "debug_traceTransaction", move |hash| match hash {
+            "0x01" => Some(serde_json::json!({
+                "from": bundler,
+                "to": entry_point,
+                "calls": [{
+                    "from": entry_point,
+                    "to": wanted,
+                    "calls": [
+                        {"from": wanted, "to": Address::repeat_byte(0x77)},
+                        {"from": wanted, "to": factory}
+                    ]
+                }]
+            })),
+            "0x03" => Some(serde_json::json!({"from": wanted, "to": entry_point})),
+            _ => None,
+        });
+
+        let mut options = DetectorOptions::default();
+        options.verification.mode = VerifyMode::Trace;
+        options.verification.timeout = Duration::from_millis(100);
+        options.verification.retries = 0;
+        let detector = TokenDetector::with_options(profile, vec![node.url()], options).unwrap();
+
+        assert_eq!(detector.verify_caller("0x01", 0).await, Verification::Verified(Initiator {
+            address: wanted,
+            path: InitiatorPath::CallFrame { chain: vec![bundler, entry_point, wanted] },
+        }));
+        assert_eq!(detector.verify_caller("0x02", 0).await, Verification::Rejected(Initiator {
+            address: unwanted,
+            path: InitiatorPath::DirectCall,
+        }));
+        assert!(matches!(
+            detector.verify_caller("0x03", 0).await,
+            Verification::Verified(Initiator { path: InitiatorPath::ReceiptSender { .. }, .. })
+        ));
+        // A hanging trace is inconclusive rather than a fallback to the sender
+        assert!(matches!(detector.verify_caller("0x04", 0).await, Verification::Unknown(_)));
+    }
+
+    #[tokio::test]
+    async fn test_trace_falls_back_to_the_sender_only_without_debug_namespace() {
+        let profile = shipped_profile();
+        let unwanted = profile.unwanted[0];
+        let entry_point = Address::repeat_byte(0xe0);
+
+        let node = MockNode::start().await;
+        on_tx(&node, "eth_getTransactionReceipt", move |_| Some(serde_json::json!({"from": unwanted, "to": entry_point})));
+        node.on("debug_traceTransaction", |params| {
+            let (code, message) = match params[0].as_str() {
+                Some("0x01") => (-32601, "the method debug_traceTransaction does not exist/is not available"),
+                _ => (-32000, "execution timeout"),
+            };
+            Reply::Error(RpcError { code, message: message.to_string() })
+        });
+
+        let mut options = DetectorOptions::default();
+        options.verification.mode = VerifyMode::Trace;
+        options.verification.retries = 0;
+        let detector = TokenDetector::with_options(profile, vec![node.url()], options).unwrap();
+
+        for _ in 0..2 {
+            assert!(matches!(
+                detector.verify_caller("0x01", 0).await,
+                Verification::Rejected(Initiator { path: InitiatorPath::ReceiptSender { .. }, .. })
+            ));
+        }
+        // The fallback is not cached as a rejection
+        assert_eq!(node.requests("debug_traceTransaction").len(), 2);
+        // A failed trace is not a missing debug namespace
+        assert!(matches!(detector.verify_caller("0x02", 0).await, Verification::Unknown(_)));
+    }
+
+    #[tokio::test]
+    async fn test_block_classifies_logs_like_live_detection() {
+        let profile = shipped_profile();
+        let (wanted, unwanted) = (profile.wanted[0], profile.unwanted[0]);
+        let node = MockNode::start().await;
+        // Wanted creator in the event, then one that needs verification and fails it
+        node.push_log(launch_log(&profile, 0x01, Address::repeat_byte(0xaa), wanted));
+        node.push_log(launch_log(&profile, 0x02, Address::repeat_byte(0xbb), Address::repeat_byte(0x22)));
+        on_tx(&node, "eth_getTransactionByHash", move |_| Some(serde_json::json!({"from": unwanted})));
+
+        let detector = TokenDetector::with_options(profile, vec![node.url()], DetectorOptions::default()).unwrap();
+        let detections = detector.test_block(0x10).await.unwrap();
+
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].token, Address::repeat_byte(0xaa));
+        assert_eq!((detections[0].rule, detections[0].confidence), (MatchRule::EventAddress, Confidence::Wanted));
+        // The dao earned the match; the tx initiator was never looked up
+        assert_eq!((detections[0].matched_address, detections[0].caller), (Some(wanted), None));
+        assert_eq!(node.requests("eth_getTransactionByHash").len(), 1);
+        assert!(detector.test_block(0x11).await.unwrap().is_empty());
+    }
+
+    // tests/fixtures/captures/<name>.json: where <name>.jsonl was recorded and what replaying it yields
+    #[derive(serde::Deserialize)]
+    struct CaptureManifest {
+        profile: String,
+        block: u64,
+        tx: ethers::types::H256,
+        events: Vec<CapturedEvent>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct CapturedEvent {
+        kind: String,
+        token: Address,
+        tx: ethers::types::H256,
+    }
+
+    fn capture_manifests() -> Vec<std::path::PathBuf> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/captures");
+        std::fs::read_dir(&dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect()
+    }
+
+    // Until a capture is committed the replay test below checks nothing; this keeps that visible
+    #[test]
+    #[ignore = "no RECORD_PATH capture committed yet: recording one needs a live Base node"]
+    fn test_captures_directory_holds_a_real_capture() {
+        assert!(!capture_manifests().is_empty(), "tests/fixtures/captures has no <name>.json manifest");
+    }
+
+    #[tokio::test]
+    async fn test_recorded_captures_replay_to_their_manifests() {
+        let config = Config::from_file(Path::new("config.toml")).unwrap();
+
+        for path in capture_manifests() {
+            let manifest: CaptureManifest = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let recording = Arc::new(Recording::from_file(&path.with_extension("jsonl")).unwrap());
+
+            let block = Value::String(format!("0x{:x}", manifest.block));
+            assert!(recording.notifications().iter().any(|n| n.result["blockNumber"] == block),
+                "{}: no log from block {}", path.display(), manifest.block);
+            assert!(manifest.events.is_empty() || manifest.events.iter().any(|e| e.tx == manifest.tx),
+                "{}: no event for tx {:?}", path.display(), manifest.tx);
+
+            let profile = config.profile(&manifest.profile).unwrap();
+            let detector = TokenDetector::from_recording(profile, DetectorOptions::default(), recording.clone()).unwrap();
+            let events: Vec<CapturedEvent> = detector.replay(&recording).await.unwrap()
+                .into_iter()
+                .map(|event| match event {
+                    DetectionEvent::Detected(d) => CapturedEvent { kind: "detected".into(), token: d.token, tx: d.position.tx_hash },
+                    DetectionEvent::Retracted(r) => CapturedEvent { kind: "retracted".into(), token: r.detection.token, tx: r.detection.position.tx_hash },
+                    DetectionEvent::Provisional(p) => CapturedEvent { kind: "provisional".into(), token: p.predicted_token, tx: p.tx_hash },
+                    DetectionEvent::Enriched(d) => CapturedEvent { kind: "enriched".into(), token: d.token, tx: d.position.tx_hash },
+                })
+                .collect();
+            assert_eq!(events, manifest.events, "{}", path.display());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_runs_a_capture_through_process_event_and_verify_caller() {
+        use crate::recording::Record;
+
+        let profile = shipped_profile();
+        let (wanted, unwanted) = (profile.wanted[0], profile.unwanted[0]);
+        let at = |secs: i64| DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap();
+        let response = |secs, method: &str, params: Value, result: Value| Record {
+            at: at(secs),
+            endpoint: 0,
+            request: Some(serde_json::json!({"method": method, "params": params})),
+            message: serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": result}),
+        };
+        let notification = |secs, endpoint, log: &Value| Record {
+            at: at(secs),
+            endpoint,
+            request: None,
+            message: serde_json::json!({"jsonrpc": "2.0", "method": "eth_subscription", "params": {"subscription": "0xlogs", "result": log}}),
+        };
+
+        let launch = launch_log(&profile, 0x01, Address::repeat_byte(0xaa), Address::repeat_byte(0x22));
+        let decoy = launch_log(&profile, 0x02, Address::repeat_byte(0xbb), Address::repeat_byte(0x22));
+        let tx_hash = |tx| Value::String(format!("{:?}", ethers::types::H256::repeat_byte(tx)));
+        let logs = serde_json::json!(["logs", {"address": profile.deployer, "topics": [profile.topic]}]);
+        let recording = Arc::new(Recording::from_records([
+            response(0, "eth_subscribe", logs.clone(), "0xlogs".into()),
+            Record { endpoint: 1, ..response(0, "eth_subscribe", logs, "0xlogs".into()) },
+            notification(1, 0, &decoy),
+            notification(2, 0, &launch),
+            // The raced second endpoint delivers the same launch later
+            notification(3, 1, &launch),
+            response(4, "eth_getTransactionByHash", serde_json::json!([tx_hash(0x02)]), serde_json::json!({"from": unwanted})),
+            // Not indexed yet on the first lookup
+            response(5, "eth_getTransactionByHash", serde_json::json!([tx_hash(0x01)]), Value::Null),
+            response(6, "eth_getTransactionByHash", serde_json::json!([tx_hash(0x01)]), serde_json::json!({"from": wanted})),
+        ]));
+
+        let mut options = DetectorOptions::default();
+        options.verification.retry_delay = Duration::from_millis(1);
+        let detector = TokenDetector::from_recording(profile, options, recording.clone()).unwrap();
+        let events = detector.replay(&recording).await.unwrap();
+
+        assert_eq!(events.len(), 1);
+        let DetectionEvent::Detected(detection) = &events[0] else {
+            panic!("expected a detection, got {:?}", events[0]);
+        };
+        assert_eq!(detection.token, Address::repeat_byte(0xaa));
+        assert_eq!((detection.matched_address, detection.caller), (Some(wanted), Some(wanted)));
+        assert_eq!((detection.rule, detection.confidence), (MatchRule::VerifiedCaller, Confidence::Verified));
+        // Capture time, not replay time
+        assert_eq!(detection.received_at, at(2));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_resubscribes_and_backfills_the_gap_once() {
+        let profile = shipped_profile();
+        let wanted = profile.wanted[0];
+        let node = MockNode::start().await;
+        node.push_log(launch_log(&profile, 0x01, Address::repeat_byte(0xaa), wanted));
+
+        let detector = TokenDetector::with_options(profile.clone(), vec![node.url()], DetectorOptions::default()).unwrap();
+        let mut detections = std::pin::pin!(detector.detections());
+        let wait = Duration::from_secs(5);
+        let Some(Ok(DetectionEvent::Detected(first))) = tokio::time::timeout(wait, detections.next()).await.unwrap() else {
+            panic!("expected the first launch");
+        };
+        assert_eq!(first.token, Address::repeat_byte(0xaa));
+
+        // Socket drops; a launch is mined while nobody is subscribed
+        node.disconnect();
+        node.push_missed_log(launch_log(&profile, 0x02, Address::repeat_byte(0xbb), wanted));
+
+        let Some(Ok(DetectionEvent::Detected(missed))) = tokio::time::timeout(wait, detections.next()).await.unwrap() else {
+            panic!("expected the backfilled launch");
+        };
+        assert_eq!(missed.token, Address::repeat_byte(0xbb));
+        assert_eq!(node.requests("eth_subscribe").len(), 2);
+        let backfills = node.requests("eth_getLogs");
+        assert_eq!(backfills.len(), 1);
+        assert_eq!(backfills[0][0]["fromBlock"], "0x10");
+
+        // The first launch comes back in the backfill but is not emitted again
+        assert!(tokio::time::timeout(Duration::from_millis(300), detections.next()).await.is_err());
+        detector.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_backfill_is_retried_from_the_same_block() {
+        let profile = shipped_profile();
+        let wanted = profile.wanted[0];
+        let node = MockNode::start().await;
+        node.push_log(launch_log(&profile, 0x01, Address::repeat_byte(0xaa), wanted));
+        // The first backfill hits a node that is still syncing
+        let failures = Arc::new(AtomicUsize::new(0));
+        {
+            let failures = failures.clone();
+            node.on("eth_getLogs", move |_| match failures.fetch_add(1, Ordering::SeqCst) {
+                0 => Reply::Error(RpcError { code: -32000, message: "header not found".to_string() }),
+                _ => Reply::Builtin,
+            });
         }
-        Err(e) => {
-            error!("Detection failed: {}", e);
-            std::process::exit(1);
+
+        let detector = TokenDetector::with_options(profile.clone(), vec![node.url()], DetectorOptions::default()).unwrap();
+        let mut detections = std::pin::pin!(detector.detections());
+        let wait = Duration::from_secs(5);
+        assert!(matches!(tokio::time::timeout(wait, detections.next()).await.unwrap(), Some(Ok(DetectionEvent::Detected(_)))));
+
+        node.disconnect();
+        node.push_missed_log(launch_log(&profile, 0x02, Address::repeat_byte(0xbb), wanted));
+
+        let Some(Ok(DetectionEvent::Detected(missed))) = tokio::time::timeout(wait, detections.next()).await.unwrap() else {
+            panic!("expected the backfilled launch");
+        };
+        assert_eq!(missed.token, Address::repeat_byte(0xbb));
+        // Both attempts cover the gap up to the head, never an open-ended range
+        let backfills = node.requests("eth_getLogs");
+        assert_eq!(backfills.len(), 2);
+        for backfill in &backfills {
+            assert_eq!((&backfill[0]["fromBlock"], &backfill[0]["toBlock"]), (&Value::from("0x10"), &Value::from("0x10")));
+        }
+        detector.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_stop_ends_the_stream_on_a_quiet_chain() {
+        let node = MockNode::start().await;
+        let detector = TokenDetector::with_options(shipped_profile(), vec![node.url()], DetectorOptions::default()).unwrap();
+        let mut detections = std::pin::pin!(detector.detections());
+
+        // Subscribed, and nothing will ever arrive
+        for _ in 0..100 {
+            if !node.requests("eth_blockNumber").is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        detector.stop().await;
+
+        let end = tokio::time::timeout(Duration::from_secs(1), detections.next()).await;
+        assert!(matches!(end, Ok(None)), "expected the stream to end, got {:?}", end);
+        assert_eq!(node.requests("eth_unsubscribe").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_removed_log_retracts_the_detection_and_frees_the_tx() {
+        let profile = shipped_profile();
+        let node = MockNode::start().await;
+        let launch = launch_log(&profile, 0x01, Address::repeat_byte(0xaa), profile.wanted[0]);
+        node.push_log(launch.clone());
+
+        let detector = TokenDetector::with_options(profile, vec![node.url()], DetectorOptions::default()).unwrap();
+        let mut detections = std::pin::pin!(detector.detections());
+        let wait = Duration::from_secs(5);
+        let Some(Ok(DetectionEvent::Detected(detected))) = tokio::time::timeout(wait, detections.next()).await.unwrap() else {
+            panic!("expected a detection");
+        };
+
+        // The block is reorged out: the node re-sends the log flagged as removed
+        let mut removed = launch.clone();
+        removed["removed"] = Value::Bool(true);
+        node.push_log(removed);
+        let event = tokio::time::timeout(wait, detections.next()).await.unwrap();
+        let Some(Ok(DetectionEvent::Retracted(retraction))) = event else {
+            panic!("expected a retraction, got {:?}", event);
+        };
+        assert_eq!(retraction.detection.token, detected.token);
+        assert_eq!(retraction.detection.position, detected.position);
+        detector.stop().await;
+
+        // The tx is no longer marked processed, so its re-mined log is evaluated afresh
+        let tx_hash = launch["transactionHash"].as_str().unwrap().to_string();
+        assert!(!detector.processed_txs.lock().await.contains(&tx_hash));
+        let mut remined = launch;
+        remined["blockHash"] = serde_json::json!(ethers::types::H256::repeat_byte(0xcc));
+        let event = detector.process_event(&remined, Utc::now(), PRIMARY_ENDPOINT).await.unwrap();
+        assert!(matches!(event, Some(DetectionEvent::Detected(d)) if d.token == detected.token));
+    }
+
+    #[tokio::test]
+    async fn test_launch_re_mined_after_a_reorg_is_detected_again() {
+        let profile = shipped_profile();
+        let node = MockNode::start().await;
+        let launch = launch_log(&profile, 0x01, Address::repeat_byte(0xaa), profile.wanted[0]);
+        let mut removed = launch.clone();
+        removed["removed"] = Value::Bool(true);
+        // Same tx and log index in the replacement block
+        let mut remined = launch.clone();
+        remined["blockHash"] = serde_json::json!(ethers::types::H256::repeat_byte(0xcc));
+
+        let detector = TokenDetector::with_options(profile, vec![node.url()], DetectorOptions::default()).unwrap();
+        let mut detections = std::pin::pin!(detector.detections());
+        let wait = Duration::from_secs(5);
+
+        node.push_log(launch);
+        assert!(matches!(tokio::time::timeout(wait, detections.next()).await.unwrap(), Some(Ok(DetectionEvent::Detected(_)))));
+        node.push_log(removed);
+        assert!(matches!(tokio::time::timeout(wait, detections.next()).await.unwrap(), Some(Ok(DetectionEvent::Retracted(_)))));
+        node.push_log(remined);
+        let event = tokio::time::timeout(wait, detections.next()).await.unwrap();
+        let Some(Ok(DetectionEvent::Detected(detection))) = event else {
+            panic!("expected the re-mined launch, got {:?}", event);
+        };
+        assert_eq!(detection.position.block_hash, Some(ethers::types::H256::repeat_byte(0xcc)));
+        detector.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_mempool_mode_emits_provisional_launch() {
+        let profile = shipped_profile();
+        let launch = ExecuteApplicationCall { id: 42.into(), can_stake: true }.encode();
+        let wanted = profile.wanted[0];
+
+        let node = MockNode::start().await;
+        node.respond("eth_getTransactionCount", "0x5".into());
+        // Not the factory, unwanted sender, then the launch we want
+        node.push_pending_tx(pending_tx(0x01, wanted, Address::repeat_byte(0x99), launch.clone()));
+        node.push_pending_tx(pending_tx(0x02, profile.unwanted[0], profile.deployer, launch.clone()));
+        node.push_pending_tx(pending_tx(0x03, wanted, profile.deployer, launch));
+
+        let options = DetectorOptions { use_mempool: true, ..Default::default() };
+        let detector = TokenDetector::with_options(profile.clone(), vec![node.url()], options).unwrap();
+        let mut detections = std::pin::pin!(detector.detections());
+
+        let event = tokio::time::timeout(Duration::from_secs(5), detections.next()).await.unwrap();
+        let Some(Ok(DetectionEvent::Provisional(launch))) = event else {
+            panic!("expected a provisional launch, got {:?}", event);
+        };
+        assert_eq!(launch.tx_hash, ethers::types::H256::repeat_byte(0x03));
+        assert_eq!(launch.creator, wanted);
+        assert_eq!(launch.call.application_id, 42.into());
+        assert_eq!(launch.predicted_token, ethers::utils::get_contract_address(profile.deployer, 5u64));
+        let nonce_reads = node.requests("eth_getTransactionCount");
+        assert_eq!(nonce_reads[0][1], "pending");
+        assert!(launch.to_string().contains("unverified"));
+        assert_eq!(launch.rule, MatchRule::PendingSender);
+
+        detector.stop().await;
     }
 }
@@ -0,0 +1,490 @@
+use anyhow::Result;
+use chrono::Utc;
+use ethers::prelude::*;
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::{AbortHandle, JoinSet};
+use tracing::{info, warn, error};
+
+use crate::bytecode::fetch_risk_report;
+use crate::config::Profile;
+use crate::detection::{Detection, DetectionEvent};
+use crate::detector::TokenDetector;
+use crate::honeypot::{simulate_round_trip, HoneypotVerdict};
+use crate::latency::{LatencyStats, SwapTimings};
+use crate::liquidity::{wait_for_liquidity, Liquidity};
+use crate::uniswap::{UniswapTrader, GasConfig, get_deadline_from_now};
+
+// Signing client the sniping binaries trade through
+pub type SignerClient = SignerMiddleware<Provider<Ws>, LocalWallet>;
+
+// Buys waiting on their checks or receipt at once; launches beyond that are skipped
+const MAX_PENDING_BUYS: usize = 8;
+
+// A finished buy task: the detection, what execute_swap did, and the launch block's timestamp
+type FinishedBuy = (Detection, Result<SwapOutcome>, Option<u64>);
+
+// What execute_swap did with a detection
+#[derive(Debug)]
+pub enum SwapOutcome {
+    Sent(SwapTimings),
+    // A pre-trade check vetoed the buy
+    Skipped(String),
+}
+
+// Setup wallet and provider for swapping using WebSocket (faster for sniping)
+pub async fn connect_signer(wss_url: &str, private_key: &str, chain_id: u64) -> Result<Arc<SignerClient>> {
+    let provider = Provider::<Ws>::connect(wss_url).await?;
+    let wallet: LocalWallet = private_key.parse()?;
+    
+    // Set the chain ID from the active profile
+    let wallet = wallet.with_chain_id(chain_id);
+    
+    Ok(Arc::new(SignerMiddleware::new(provider, wallet)))
+}
+
+// Live pipeline: swap into every qualifying launch until Ctrl-C or the detector gives up
+pub async fn snipe_live<M: Middleware + 'static>(
+    detector: &TokenDetector,
+    trader: &UniswapTrader<M>,
+    profile: &Profile,
+    recipient: Address
+) -> Result<()>
+where
+    M::Provider: PubsubClient,
+{
+    // Snipe every qualifying launch until Ctrl-C
+    let mut detections = std::pin::pin!(detector.detections());
+    let mut latency = LatencyStats::default();
+    // One listener for the whole session, so a Ctrl-C is seen while buys are in flight
+    let mut shutdown = std::pin::pin!(tokio::signal::ctrl_c());
+    // Each buy runs its checks and swap in its own task, so a slow pool never holds up detection.
+    // Tasks are tracked by launch, so a reorg can cancel a buy that hasn't gone out yet.
+    let (trader, profile) = (Arc::new(trader.clone()), Arc::new(profile.clone()));
+    let mut buys: JoinSet<FinishedBuy> = JoinSet::new();
+    let mut pending: HashMap<(Option<H256>, H256), AbortHandle> = HashMap::new();
+    loop {
+        tokio::select! {
+            detection = detections.next() => match detection {
+                Some(Ok(DetectionEvent::Detected(detection))) => {
+                    if buys.len() >= MAX_PENDING_BUYS {
+                        warn!("⏭️ Skipped token {:?}: {} buys already in flight", detection.token, buys.len());
+                        continue;
+                    }
+                    info!("🎯 TOKEN DETECTED: {} - Executing immediate swap (decided in {}ms)",
+                          detection, detection.decision_latency().num_milliseconds());
+                    
+                    let launch = launch_key(&detection);
+                    let (trader, profile) = (trader.clone(), profile.clone());
+                    let buy = buys.spawn(async move {
+                        let outcome = execute_swap(&trader, &profile, &detection, recipient).await;
+                        // Off the hot path: most nodes leave blockTimestamp out of logs
+                        let block_timestamp = match detection.position.block_timestamp {
+                            Some(timestamp) => Some(timestamp),
+                            None => block_timestamp(&trader, detection.position.block_number).await,
+                        };
+                        (detection, outcome, block_timestamp)
+                    });
+                    pending.insert(launch, buy);
+                }
+                Some(Ok(DetectionEvent::Provisional(launch))) => {
+                    // Nothing to buy until the log is mined; make sure the swap won't revert on allowance
+                    info!("🔮 PENDING LAUNCH: {} - waiting for it to be mined", launch);
+                    warn_if_underapproved(&trader, &profile, recipient).await;
+                }
+                // The detector logs the metadata; the swap never waits for it
+                Some(Ok(DetectionEvent::Enriched(_))) => {}
+                Some(Ok(DetectionEvent::Retracted(retraction))) => match pending.remove(&launch_key(&retraction.detection)) {
+                    Some(buy) => {
+                        // A swap already broadcast cannot be undone; anything earlier is dropped
+                        buy.abort();
+                        warn!("↩️ Launch retracted by reorg - cancelled its buy unless already sent: {}", retraction.detection);
+                    }
+                    None => {
+                        // A sent swap cannot be undone; surface it so the position can be reviewed
+                        warn!("↩️ Launch retracted by reorg: {}", retraction.detection);
+                    }
+                },
+                Some(Err(e)) => {
+                    error!("❌ Live detection failed: {}", e);
+                    break;
+                }
+                None => {
+                    info!("❌ Live detection ended");
+                    break;
+                }
+            },
+            Some(finished) = buys.join_next(), if !buys.is_empty() => {
+                if let Some(detection) = record_buy(&mut latency, finished) {
+                    pending.remove(&launch_key(&detection));
+                }
+            }
+            _ = &mut shutdown => {
+                info!("🛑 Shutdown requested - stopping detection and {} pending buys", buys.len());
+                detector.stop().await;
+                buys.abort_all();
+                break;
+            }
+        }
+    }
+    
+    // Buys still under way finish unless Ctrl-C aborted them
+    while let Some(finished) = buys.join_next().await {
+        record_buy(&mut latency, finished);
+    }
+    
+    // Endpoint race results, to spot providers worth dropping
+    for stats in detector.endpoint_stats().await {
+        info!("🏁 {}", stats);
+    }
+    for stats in detector.cache_stats().await {
+        info!("🗃️ {}", stats);
+    }
+    // Where the milliseconds went, stage by stage
+    for line in latency.summary() {
+        info!("⏱️ {}", line);
+    }
+    
+    Ok(())
+}
+
+// A launch log's identity; a re-mined launch has the same tx in another block
+fn launch_key(detection: &Detection) -> (Option<H256>, H256) {
+    (detection.position.block_hash, detection.position.tx_hash)
+}
+
+// Log a finished buy task and time it; returns its detection unless the task was cancelled
+fn record_buy(latency: &mut LatencyStats, finished: Result<FinishedBuy, tokio::task::JoinError>) -> Option<Detection> {
+    let (detection, outcome, block_timestamp) = match finished {
+        Ok(finished) => finished,
+        Err(e) if e.is_cancelled() => return None,
+        Err(e) => {
+            error!("❌ Buy task failed: {}", e);
+            return None;
+        }
+    };
+    
+    match outcome {
+        Ok(SwapOutcome::Sent(timings)) => {
+            info!("✅ Swap execution completed for token: {:?}", detection.token);
+            latency.record_swap(&detection, &timings);
+        }
+        Ok(SwapOutcome::Skipped(reason)) => warn!("⏭️ Skipped token {:?}: {}", detection.token, reason),
+        Err(e) => error!("❌ Swap failed for token {:?}: {}", detection.token, e),
+    }
+    latency.record_detection(&detection, block_timestamp);
+    Some(detection)
+}
+
+async fn block_timestamp<M: Middleware + 'static>(trader: &UniswapTrader<M>, block: Option<u64>) -> Option<u64> {
+    let block = trader.client().get_block(block?).await.ok()??;
+    Some(block.timestamp.as_u64())
+}
+
+// Pre-flight for a provisional launch: the input token must already be approved for the router
+async fn warn_if_underapproved<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    profile: &Profile,
+    owner: Address
+) {
+    let result = async {
+        let amount_in = profile.buy_amount()?;
+        let allowance = trader.check_allowance(profile.input_token, owner).await?;
+        anyhow::Ok((amount_in, allowance))
+    }.await;
+    
+    match result {
+        Ok((amount_in, allowance)) if allowance < amount_in => {
+            warn!("⚠️ Router allowance {} is below buy size {} - the swap will revert", allowance, amount_in);
+        }
+        Ok(_) => {}
+        Err(e) => warn!("⚠️ Allowance check failed: {}", e),
+    }
+}
+
+// Simulated buy/approve/sell; a token that can't be simulated is vetoed too
+async fn honeypot_veto<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    profile: &Profile,
+    token: Address,
+    amount_in: U256,
+    account: Address,
+    liquidity: &Liquidity
+) -> Option<String> {
+    let verdict = simulate_round_trip(
+        trader.client(),
+        trader.router_address(),
+        profile.input_token,
+        token,
+        amount_in,
+        account,
+        (liquidity.reserve_in, liquidity.reserve_out),
+        profile.honeypot_max_tax_bps,
+    ).await;
+    
+    match verdict {
+        Ok(HoneypotVerdict::Tradable(round_trip)) => {
+            info!("🍯 Honeypot check passed: {}", round_trip);
+            None
+        }
+        Ok(HoneypotVerdict::Honeypot(reason)) => Some(format!("honeypot ({})", reason)),
+        Err(e) => Some(format!("honeypot check failed ({})", e)),
+    }
+}
+
+// Static code analysis against the profile's veto_risks; unreadable code is vetoed too
+async fn bytecode_veto<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    profile: &Profile,
+    token: Address
+) -> Option<String> {
+    let report = match fetch_risk_report(trader.client().as_ref(), token).await {
+        Ok(report) => report,
+        Err(e) => return Some(format!("bytecode analysis failed ({})", e)),
+    };
+    
+    let vetoed = report.vetoed_by(&profile.veto_risks);
+    if vetoed.is_empty() {
+        info!("🔬 Bytecode check passed: {}", report);
+        return None;
+    }
+    let evidence: Vec<String> = vetoed.iter().map(|f| format!("{} ({})", f.risk, f.evidence)).collect();
+    Some(format!("risky bytecode: {}", evidence.join(", ")))
+}
+
+// Hold the buy until the pool exists with enough input reserve; a timeout vetoes it
+async fn liquidity_veto<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    profile: &Profile,
+    token: Address
+) -> Result<Liquidity, String>
+where
+    M::Provider: PubsubClient,
+{
+    let liquidity = async {
+        wait_for_liquidity(
+            trader.client(),
+            trader.factory_address(),
+            profile.input_token,
+            token,
+            profile.min_liquidity()?,
+            std::time::Duration::from_secs(profile.liquidity_timeout_secs),
+        ).await
+    }.await;
+    
+    match liquidity {
+        Ok(liquidity) => {
+            info!("💧 Liquidity ready: {}", liquidity);
+            Ok(liquidity)
+        }
+        Err(e) => Err(format!("liquidity not ready ({})", e)),
+    }
+}
+
+pub async fn execute_swap<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    profile: &Profile,
+    detection: &Detection,
+    recipient: Address
+) -> Result<SwapOutcome>
+where
+    M::Provider: PubsubClient,
+{
+    let start_time = std::time::Instant::now();
+    let started_at = Utc::now();
+    
+    let token_out = detection.token;
+    
+    // Configuration - Input token, size and gas from the active profile
+    let amount_in = profile.buy_amount()?;
+    let gas_config = GasConfig::preset(&profile.gas_preset)
+        .ok_or_else(|| anyhow::anyhow!("Unknown gas preset: {}", profile.gas_preset))?;
+    let path = vec![profile.input_token, token_out];
+    let deadline = get_deadline_from_now(300); // 5 minutes
+    
+    // Minimum amount out (allowing for slippage)
+    let amount_out_min = U256::from(1); // Accept any amount of output tokens
+    
+    // Pre-trade checks: bytecode runs while the pool fills; the simulation needs the pool.
+    // Any veto skips the token.
+    let (bytecode, market) = tokio::join!(
+        async {
+            if profile.veto_risks.is_empty() {
+                return None;
+            }
+            bytecode_veto(trader, profile, token_out).await
+        },
+        async {
+            let liquidity = match liquidity_veto(trader, profile, token_out).await {
+                Ok(liquidity) => liquidity,
+                Err(reason) => return Some(reason),
+            };
+            if !profile.honeypot_check {
+                return None;
+            }
+            // Priced on the reserves the buy is released against
+            honeypot_veto(trader, profile, token_out, amount_in, recipient, &liquidity).await
+        }
+    );
+    if let Some(reason) = bytecode.or(market) {
+        return Ok(SwapOutcome::Skipped(reason));
+    }
+    let checked_at = Utc::now();
+    
+    // Execute swap immediately - NO LOGS BETWEEN THE CHECKS AND THIS POINT
+    let sent = trader.swap_exact_tokens_for_tokens_timed(
+        amount_in,
+        amount_out_min,
+        path,
+        recipient,
+        deadline,
+        Some(gas_config)
+    ).await?;
+    
+    let execution_time = start_time.elapsed();
+    let receipt = &sent.receipt;
+    let receipt_block = receipt.block_number.map(|b| b.as_u64());
+    
+    // Log detailed transaction information after swap is sent
+    info!("🎯 SWAP SENT! Hash: {}", receipt.transaction_hash);
+    info!("⚡ Execution Time: {:?}", execution_time);
+    info!("⛽ Gas Used: {}", receipt.gas_used.unwrap_or_default());
+    info!("🎯 Block: {} ({} after launch)",
+          receipt_block.unwrap_or_default(),
+          match (receipt_block, detection.position.block_number) {
+              (Some(ours), Some(launch)) => format!("{} blocks", ours.saturating_sub(launch)),
+              _ => "? blocks".to_string(),
+          });
+    info!("💰 Token: {:?} (block {:?}, launch tx {:?})", token_out, detection.position.block_number, detection.position.tx_hash);
+    info!("🔗 Explorer: https://basescan.org/tx/{}", receipt.transaction_hash);
+    
+    Ok(SwapOutcome::Sent(SwapTimings {
+        tx_hash: receipt.transaction_hash,
+        started_at,
+        checked_at,
+        signed_at: sent.signed_at,
+        broadcast_at: sent.broadcast_at,
+        confirmed_at: sent.confirmed_at,
+        receipt_block,
+    }))
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, DetectorOptions};
+    use crate::detection::MatchRule;
+    use crate::liquidity::{GetPairCall, GetReservesCall, PairCreatedFilter};
+    use crate::mock_node::{launch_log, output, MockNode, Reply};
+    use crate::uniswap::{FactoryCall, SwapExactTokensForTokensCall};
+    use ethers::abi::{AbiDecode, Token};
+    use ethers::contract::{EthCall, EthEvent};
+    use std::path::Path;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_detect_verify_and_swap_against_mock_node() {
+        let mut profile = Config::from_file(Path::new("config.toml"))
+            .and_then(|config| config.profile(&config.default_profile))
+            .unwrap();
+        profile.honeypot_check = false;
+        profile.veto_risks.clear();
+        let wanted = profile.wanted[0];
+        let (token, factory, pair) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xfa), Address::repeat_byte(0x9a));
+
+        // A launch that needs its caller verified, and a funded pool for it
+        let node = MockNode::start().await;
+        node.push_log(launch_log(&profile, 0x01, token, Address::repeat_byte(0x22)));
+        let launch_tx = format!("{:?}", H256::repeat_byte(0x01));
+        node.on("eth_getTransactionByHash", move |params| match params[0].as_str() {
+            Some(hash) if hash == launch_tx => Reply::Result(serde_json::json!({"from": wanted})),
+            _ => Reply::Builtin,
+        });
+        node.on_call(profile.router, FactoryCall::selector(), output(&[Token::Address(factory)]));
+        node.on_call(factory, GetPairCall::selector(), output(&[Token::Address(pair)]));
+        node.on_call(pair, GetReservesCall::selector(), output(&[Token::Uint(1_000.into()), Token::Uint(5_000.into()), Token::Uint(0.into())]));
+
+        let detector = TokenDetector::with_options(profile.clone(), vec![node.url()], DetectorOptions::default()).unwrap();
+        let mut detections = std::pin::pin!(detector.detections());
+        let event = tokio::time::timeout(Duration::from_secs(5), detections.next()).await.unwrap();
+        let Some(Ok(DetectionEvent::Detected(detection))) = event else {
+            panic!("expected a detection, got {:?}", event);
+        };
+        detector.stop().await;
+        assert_eq!((detection.token, detection.caller, detection.rule), (token, Some(wanted), MatchRule::VerifiedCaller));
+
+        // Anvil's first dev key
+        let wallet: LocalWallet = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let recipient = wallet.address();
+        let provider = Provider::<Ws>::connect(node.url()).await.unwrap().interval(Duration::from_millis(10));
+        let client = Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(profile.chain_id)));
+        let trader = UniswapTrader::with_router(client, profile.router).await.unwrap();
+
+        let outcome = execute_swap(&trader, &profile, &detection, recipient).await.unwrap();
+        let SwapOutcome::Sent(timings) = outcome else {
+            panic!("expected a sent swap, got {:?}", outcome);
+        };
+        assert_eq!(timings.receipt_block, Some(0x10));
+
+        let sent = node.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert_eq!((sent[0].hash, sent[0].from, sent[0].tx.to_addr()), (timings.tx_hash, recipient, Some(&profile.router)));
+        let swap = SwapExactTokensForTokensCall::decode(sent[0].tx.data().unwrap()).unwrap();
+        assert_eq!(swap.amount_in, profile.buy_amount().unwrap());
+        assert_eq!(swap.path, vec![profile.input_token, token]);
+        assert_eq!(swap.to, recipient);
+
+        // The factory was read when the trader was built, not per buy
+        execute_swap(&trader, &profile, &detection, recipient).await.unwrap();
+        let factory_reads = node.requests("eth_call").iter()
+            .filter_map(|params| serde_json::from_value::<Bytes>(params[0]["data"].clone()).ok())
+            .filter(|data| data.starts_with(&FactoryCall::selector()))
+            .count();
+        assert_eq!(factory_reads, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retracted_launch_cancels_its_pending_buy() {
+        let mut profile = Config::from_file(Path::new("config.toml"))
+            .and_then(|config| config.profile(&config.default_profile))
+            .unwrap();
+        profile.honeypot_check = false;
+        profile.veto_risks.clear();
+        profile.liquidity_timeout_secs = 60;
+        let (token, factory) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xfa));
+        let launch = launch_log(&profile, 0x01, token, profile.wanted[0]);
+        let mut removed = launch.clone();
+        removed["removed"] = serde_json::Value::Bool(true);
+
+        // No pair yet, so the buy waits on PairCreated
+        let node = MockNode::start().await;
+        node.on_call(profile.router, FactoryCall::selector(), output(&[Token::Address(factory)]));
+        node.on_call(factory, GetPairCall::selector(), output(&[Token::Address(Address::zero())]));
+
+        let detector = TokenDetector::with_options(profile.clone(), vec![node.url()], DetectorOptions::default()).unwrap();
+        let wallet: LocalWallet = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let recipient = wallet.address();
+        let provider = Provider::<Ws>::connect(node.url()).await.unwrap();
+        let client = Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(profile.chain_id)));
+        let trader = UniswapTrader::with_router(client, profile.router).await.unwrap();
+
+        let waiting_on_pool = || node.requests("eth_subscribe").iter()
+            .any(|params| params[1]["topics"][0] == serde_json::json!(PairCreatedFilter::signature()));
+        let script = async {
+            node.push_log(launch);
+            while !waiting_on_pool() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            // The detection loop is free while the buy waits, so the reorg gets through
+            node.push_log(removed);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            detector.stop().await;
+        };
+        // Without the cancellation the stopped session would wait out liquidity_timeout_secs
+        let (result, _) = tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::join!(snipe_live(&detector, &trader, &profile, recipient), script)
+        }).await.unwrap();
+        result.unwrap();
+        assert!(node.sent_transactions().is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+use anyhow::{Result, anyhow};
+use ethers::abi::AbiDecode;
+use ethers::contract::ContractError;
+use ethers::prelude::*;
+use ethers::providers::{spoof, RpcError};
+use ethers::utils::keccak256;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::uniswap::{get_deadline_from_now, ERC20Token, UniswapV2Router};
+
+// Plain Solidity mapping slots tried when locating balances/allowances
+const PROBE_SLOTS: u64 = 32;
+
+// OpenZeppelin v5 upgradeable ERC20 (ERC-7201 namespace); _balances is the first field, _allowances the second
+const OZ_ERC20_STORAGE: &str = "0x52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00";
+
+// Probe values are MARKER + candidate index, far above any real balance
+fn probe_marker() -> U256 {
+    U256::from(0xfeed_u64) << 128
+}
+
+// Result of the simulated round trip
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoundTrip {
+    pub amount_in: U256,
+    pub tokens_out: U256,
+    // Selling tokens_out right after the buy, without any token tax
+    pub expected_back: U256,
+    // expected_back less the loss measured on the simulated sell
+    pub amount_back: U256,
+    // Sell output below the router's own quote, i.e. tax and transfer loss only
+    pub tax_bps: u32,
+}
+
+impl fmt::Display for RoundTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in -> {} tokens -> {} back, {} without tax ({:.2}% tax)",
+            self.amount_in, self.tokens_out, self.amount_back, self.expected_back, self.tax_bps as f64 / 100.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum HoneypotVerdict {
+    Tradable(RoundTrip),
+    Honeypot(String),
+}
+
+// Where a token keeps its ERC20 mappings
+#[derive(Debug, Clone, Copy)]
+struct TokenSlots {
+    balances: H256,
+    allowances: H256,
+}
+
+// keccak256(abi.encode(key, slot)) - storage location of mapping[key]
+fn mapping_key(key: Address, slot: H256) -> H256 {
+    let mut preimage = [0u8; 64];
+    preimage[12..32].copy_from_slice(key.as_bytes());
+    preimage[32..].copy_from_slice(slot.as_bytes());
+    H256(keccak256(preimage))
+}
+
+fn balance_key(slot: H256, owner: Address) -> H256 {
+    mapping_key(owner, slot)
+}
+
+fn allowance_key(slot: H256, owner: Address, spender: Address) -> H256 {
+    mapping_key(spender, mapping_key(owner, slot))
+}
+
+fn word(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256(bytes)
+}
+
+fn candidate_slots(namespace_offset: u64) -> Vec<H256> {
+    let namespace = U256::from_big_endian(
+        OZ_ERC20_STORAGE.parse::<H256>().expect("valid ERC-7201 slot").as_bytes(),
+    );
+    (0..PROBE_SLOTS)
+        .map(H256::from_low_u64_be)
+        .chain(std::iter::once(word(namespace + namespace_offset)))
+        .collect()
+}
+
+// Share of `expected` missing from `actual`, in basis points
+fn loss_bps(expected: U256, actual: U256) -> u32 {
+    if expected.is_zero() || actual >= expected {
+        return 0;
+    }
+    ((expected - actual) * 10_000u64 / expected).as_u32()
+}
+
+// Price impact is priced in by the post-buy expectation; only the sell's shortfall
+// against its no-tax quote counts towards max_tax_bps
+fn round_trip(amount_in: U256, tokens_out: U256, sold: U256, quoted: U256, expected_back: U256) -> RoundTrip {
+    let amount_back = if quoted.is_zero() { U256::zero() } else { expected_back * sold.min(quoted) / quoted };
+    RoundTrip {
+        amount_in,
+        tokens_out,
+        expected_back,
+        amount_back,
+        tax_bps: loss_bps(quoted, sold),
+    }
+}
+
+fn judge(round_trip: RoundTrip, max_tax_bps: u32) -> HoneypotVerdict {
+    if round_trip.amount_back.is_zero() {
+        return HoneypotVerdict::Honeypot(format!("sell returns nothing: {}", round_trip));
+    }
+    if round_trip.tax_bps > max_tax_bps {
+        return HoneypotVerdict::Honeypot(format!("sell tax above {} bps: {}", max_tax_bps, round_trip));
+    }
+    HoneypotVerdict::Tradable(round_trip)
+}
+
+// Reverts are verdicts; anything else means the simulation itself failed.
+// Nodes report reverts without data as a bare JSON-RPC error, so any error response counts.
+fn reverted<M: Middleware>(e: &ContractError<M>) -> bool {
+    e.is_revert() || e.as_provider_error().is_some_and(|e| RpcError::as_error_response(e).is_some())
+}
+
+// Simulates buy, approve and full sell for `account` with eth_call, overriding balances and
+// allowances so nothing needs to be funded. Each call runs on current state, so the sell is
+// measured against the router's quote on that same state; the post-buy outcome then comes
+// from getAmountOut on the pair's reserves as moved by the buy. `reserves` are the pair's
+// (input side, token side). Err means the token could not be simulated.
+// The sell never sees the buy's side effects, so restrictions applied on buy (blacklisting
+// the buyer, sell cooldowns, maxWallet on the balance just bought) are not detected.
+#[allow(clippy::too_many_arguments)]
+pub async fn simulate_round_trip<M: Middleware + 'static>(
+    client: Arc<M>,
+    router: Address,
+    input_token: Address,
+    token: Address,
+    amount_in: U256,
+    account: Address,
+    reserves: (U256, U256),
+    max_tax_bps: u32,
+) -> Result<HoneypotVerdict> {
+    let (input_slots, token_slots) = futures_util::try_join!(
+        locate_slots(client.clone(), input_token, account, router),
+        locate_slots(client.clone(), token, account, router),
+    )?;
+
+    let router_contract = UniswapV2Router::new(router, client.clone());
+    let deadline = get_deadline_from_now(300);
+
+    // Buy: fund the account with input_token and approve the router
+    let mut state = spoof::state();
+    state
+        .account(input_token)
+        .store(balance_key(input_slots.balances, account), word(amount_in))
+        .store(allowance_key(input_slots.allowances, account, router), word(amount_in));
+    let buy = router_contract
+        .swap_exact_tokens_for_tokens(amount_in, U256::one(), vec![input_token, token], account, deadline)
+        .from(account);
+    let tokens_out = match buy.call_raw().state(&state).await {
+        Ok(amounts) => amounts.last().copied().unwrap_or_default(),
+        Err(e) if reverted(&e) => return Ok(HoneypotVerdict::Honeypot(format!("buy reverted: {}", e))),
+        Err(e) => return Err(anyhow!("Buy simulation failed: {}", e)),
+    };
+    if tokens_out.is_zero() {
+        return Ok(HoneypotVerdict::Honeypot("buy returns no tokens".to_string()));
+    }
+
+    // Approve: some honeypots block approvals for anyone but the deployer
+    let approve = ERC20Token::new(token, client.clone())
+        .approve(router, tokens_out)
+        .from(account);
+    match approve.call_raw_bytes().await {
+        Ok(output) if output.is_empty() || bool::decode(&output).unwrap_or(false) => {}
+        Ok(output) => return Ok(HoneypotVerdict::Honeypot(format!("approve returned {}", output))),
+        Err(e) if RpcError::as_error_response(&e).is_some() => {
+            return Ok(HoneypotVerdict::Honeypot(format!("approve reverted: {}", e)));
+        }
+        Err(e) => return Err(anyhow!("Approve simulation failed: {}", e)),
+    }
+
+    // Sell everything bought back into input_token
+    let mut state = spoof::state();
+    state
+        .account(token)
+        .store(balance_key(token_slots.balances, account), word(tokens_out))
+        .store(allowance_key(token_slots.allowances, account, router), word(tokens_out));
+    let sell = router_contract
+        .swap_exact_tokens_for_tokens(tokens_out, U256::one(), vec![token, input_token], account, deadline)
+        .from(account);
+    let sold = match sell.call_raw().state(&state).await {
+        Ok(amounts) => amounts.last().copied().unwrap_or_default(),
+        Err(e) if reverted(&e) => return Ok(HoneypotVerdict::Honeypot(format!("sell reverted: {}", e))),
+        Err(e) => return Err(anyhow!("Sell simulation failed: {}", e)),
+    };
+
+    // No-tax sell on the same state, and on the reserves after the buy
+    let (reserve_in, reserve_out) = reserves;
+    let quote_call = router_contract.get_amounts_out(tokens_out, vec![token, input_token]);
+    let expected_call = router_contract.get_amount_out(
+        tokens_out,
+        reserve_out.saturating_sub(tokens_out),
+        reserve_in.saturating_add(amount_in),
+    );
+    let (quoted, expected_back) = futures_util::try_join!(quote_call.call(), expected_call.call())
+        .map_err(|e| anyhow!("Sell quote failed: {}", e))?;
+    let quoted = quoted.last().copied().unwrap_or_default();
+
+    Ok(judge(round_trip(amount_in, tokens_out, sold, quoted, expected_back), max_tax_bps))
+}
+
+// Find the balance and allowance mapping slots with one eth_call each: every candidate
+// slot gets a distinct marker value and the view reports which one it read
+async fn locate_slots<M: Middleware + 'static>(
+    client: Arc<M>,
+    token: Address,
+    owner: Address,
+    spender: Address,
+) -> Result<TokenSlots> {
+    let contract = ERC20Token::new(token, client);
+    let balance_candidates = candidate_slots(0);
+    let allowance_candidates = candidate_slots(1);
+
+    let mut balance_state = spoof::state();
+    for (i, slot) in balance_candidates.iter().enumerate() {
+        balance_state.account(token).store(balance_key(*slot, owner), word(probe_marker() + i));
+    }
+    let mut allowance_state = spoof::state();
+    for (i, slot) in allowance_candidates.iter().enumerate() {
+        allowance_state.account(token).store(allowance_key(*slot, owner, spender), word(probe_marker() + i));
+    }
+
+    let balance_call = contract.balance_of(owner);
+    let allowance_call = contract.allowance(owner, spender);
+    let (balance, allowance) = futures_util::join!(
+        balance_call.call_raw().state(&balance_state),
+        allowance_call.call_raw().state(&allowance_state),
+    );
+
+    let pick = |value: U256, candidates: &[H256], what: &str| {
+        value
+            .checked_sub(probe_marker())
+            .filter(|i| *i < U256::from(candidates.len()))
+            .map(|i| candidates[i.as_usize()])
+            .ok_or_else(|| anyhow!("Could not locate the {} slot of {:?}", what, token))
+    };
+
+    Ok(TokenSlots {
+        balances: pick(balance.map_err(|e| anyhow!("balanceOf probe failed for {:?}: {}", token, e))?, &balance_candidates, "balance")?,
+        allowances: pick(allowance.map_err(|e| anyhow!("allowance probe failed for {:?}: {}", token, e))?, &allowance_candidates, "allowance")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_node::{output, MockNode, Reply};
+    use crate::uniswap::{AllowanceCall, ApproveCall, BalanceOfCall, GetAmountOutCall, GetAmountsOutCall, SwapExactTokensForTokensCall};
+    use ethers::abi::Token;
+    use ethers::contract::EthCall;
+    use std::time::Duration;
+
+    // Uniswap V2 getAmountOut (0.3% fee)
+    fn amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256) -> U256 {
+        let with_fee = amount_in * 997;
+        with_fee * reserve_out / (reserve_in * 1000 + with_fee)
+    }
+
+    // Router over one input/token pair at fixed reserves; sells pay out `sell_bps` of the quote.
+    // Every balance/allowance probe reads the first candidate slot.
+    fn mock_pair(node: &MockNode, input_token: Address, reserves: (U256, U256), sell_bps: u64) {
+        let (reserve_in, reserve_out) = reserves;
+        let amounts = |a: U256, b: U256| output(&[Token::Array(vec![Token::Uint(a), Token::Uint(b)])]);
+        node.on("eth_call", move |params| {
+            let data: Bytes = serde_json::from_value(params[0]["data"].clone()).unwrap_or_default();
+            let selector: [u8; 4] = data.get(..4).and_then(|s| s.try_into().ok()).unwrap_or_default();
+            let result = if selector == BalanceOfCall::selector() || selector == AllowanceCall::selector() {
+                output(&[Token::Uint(probe_marker())])
+            } else if selector == ApproveCall::selector() {
+                output(&[Token::Bool(true)])
+            } else if let Ok(swap) = SwapExactTokensForTokensCall::decode(&data) {
+                let a = swap.amount_in;
+                if swap.path[0] == input_token {
+                    amounts(a, amount_out(a, reserve_in, reserve_out))
+                } else {
+                    amounts(a, amount_out(a, reserve_out, reserve_in) * sell_bps / 10_000)
+                }
+            } else if let Ok(quote) = GetAmountsOutCall::decode(&data) {
+                amounts(quote.amount_in, amount_out(quote.amount_in, reserve_out, reserve_in))
+            } else if let Ok(quote) = GetAmountOutCall::decode(&data) {
+                output(&[Token::Uint(amount_out(quote.amount_in, quote.reserve_in, quote.reserve_out))])
+            } else {
+                return Reply::Builtin;
+            };
+            Reply::Result(serde_json::Value::String(result.to_string()))
+        });
+    }
+
+    #[tokio::test]
+    async fn test_large_tax_free_buy_is_tradable_and_a_sell_tax_is_not() {
+        let (router, input_token, token, account) = (
+            Address::repeat_byte(0x10), Address::repeat_byte(0x11), Address::repeat_byte(0x22), Address::repeat_byte(0x33),
+        );
+        // A buy of 10% of the input reserve moves the price a lot
+        let reserves = (U256::from(10_000), U256::from(50_000));
+        let amount_in = U256::from(1_000);
+
+        for (sell_bps, tradable) in [(10_000, true), (8_000, false)] {
+            let node = MockNode::start().await;
+            mock_pair(&node, input_token, reserves, sell_bps);
+            let provider = Provider::<Ws>::connect(node.url()).await.unwrap().interval(Duration::from_millis(10));
+
+            let verdict = simulate_round_trip(Arc::new(provider), router, input_token, token, amount_in, account, reserves, 1_500)
+                .await
+                .unwrap();
+
+            match verdict {
+                HoneypotVerdict::Tradable(trip) => {
+                    assert!(tradable, "{}", trip);
+                    // Selling back on the moved reserves; double counting the impact gave 828 (17% loss)
+                    assert_eq!(trip.tokens_out, U256::from(4_533));
+                    assert_eq!(trip.expected_back, U256::from(994));
+                    assert_eq!(trip.amount_back, U256::from(994));
+                    assert_eq!(trip.tax_bps, 0);
+                }
+                HoneypotVerdict::Honeypot(reason) => assert!(!tradable && reason.contains("sell tax"), "{}", reason),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mapping_key_matches_solidity_layout() {
+        // keccak256(abi.encode(address(1), uint256(0)))
+        let expected: H256 = "0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7d".parse().unwrap();
+        assert_eq!(balance_key(H256::zero(), Address::from_low_u64_be(1)), expected);
+    }
+
+    #[test]
+    fn test_round_trip_tax_threshold() {
+        // The sell is quoted at 1000 on current state and would return 900 after the buy
+        let trip = |sold: u64| round_trip(1_000u64.into(), 5u64.into(), sold.into(), 1_000u64.into(), 900u64.into());
+
+        assert_eq!(trip(940).tax_bps, 600);
+        assert_eq!(trip(940).amount_back, 846u64.into());
+        assert!(matches!(judge(trip(940), 1_500), HoneypotVerdict::Tradable(_)));
+        assert!(matches!(judge(trip(800), 1_500), HoneypotVerdict::Honeypot(_)));
+        assert!(matches!(judge(trip(0), 10_000), HoneypotVerdict::Honeypot(_)));
+        assert_eq!(loss_bps(1_000u64.into(), 1_200u64.into()), 0);
+    }
+}
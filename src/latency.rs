@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use ethers::types::TxHash;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::detection::Detection;
+
+// Histogram bucket upper bounds; anything slower lands in the overflow bucket
+const BUCKETS_MS: [u64; 14] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1_000, 2_000, 5_000, 10_000, 30_000];
+
+// Pipeline stages from block production to inclusion of our swap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    // Block timestamp to WebSocket receive (1s timestamp resolution)
+    BlockToReceive,
+    // Receive to classification of the launch event
+    Classify,
+    // Classification to a verified caller (only launches that needed verification)
+    Verify,
+    // Decision to execute_swap picking it up (channel wait, metadata)
+    Queue,
+    // Liquidity wait, bytecode analysis and honeypot simulation
+    Checks,
+    // Checks done to signed tx (includes nonce/fee filling)
+    Sign,
+    // Signed to accepted by the node
+    Broadcast,
+    // Broadcast to receipt
+    Inclusion,
+    // WebSocket receive to broadcast - what a faster bot beats us on
+    ReceiveToBroadcast,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::BlockToReceive => "block -> receive",
+            Stage::Classify => "classify",
+            Stage::Verify => "verify",
+            Stage::Queue => "queue",
+            Stage::Checks => "pre-trade checks",
+            Stage::Sign => "sign",
+            Stage::Broadcast => "broadcast",
+            Stage::Inclusion => "inclusion",
+            Stage::ReceiveToBroadcast => "receive -> broadcast",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Wall-clock points of one sent swap
+#[derive(Debug, Clone)]
+pub struct SwapTimings {
+    pub tx_hash: TxHash,
+    pub started_at: DateTime<Utc>,
+    pub checked_at: DateTime<Utc>,
+    pub signed_at: DateTime<Utc>,
+    pub broadcast_at: DateTime<Utc>,
+    pub confirmed_at: DateTime<Utc>,
+    pub receipt_block: Option<u64>,
+}
+
+// Log-scale latency histogram
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    counts: [u64; BUCKETS_MS.len() + 1],
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Histogram {
+    pub fn record(&mut self, sample: Duration) {
+        let ms = sample.as_millis() as u64;
+        let bucket = BUCKETS_MS.iter().position(|&bound| ms <= bound).unwrap_or(BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.total += sample;
+        self.max = self.max.max(sample);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        self.total / self.count as u32
+    }
+
+    // Upper bound of the bucket holding the p-th percentile (max for the overflow bucket)
+    pub fn percentile(&self, p: f64) -> Duration {
+        let rank = ((p / 100.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return BUCKETS_MS
+                    .get(bucket)
+                    .map(|&ms| Duration::from_millis(ms).min(self.max))
+                    .unwrap_or(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "n={}, mean {:?}, p50 <={:?}, p90 <={:?}, p99 <={:?}, max {:?}",
+            self.count,
+            self.mean(),
+            self.percentile(50.0),
+            self.percentile(90.0),
+            self.percentile(99.0),
+            self.max)
+    }
+}
+
+fn elapsed(from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+    (to - from).to_std().unwrap_or_default()
+}
+
+// Per-stage histograms for a live session, summarized on shutdown
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    stages: BTreeMap<Stage, Histogram>,
+}
+
+impl LatencyStats {
+    pub fn record(&mut self, stage: Stage, sample: Duration) {
+        self.stages.entry(stage).or_default().record(sample);
+    }
+
+    // Detector-side stages; `block_timestamp` falls back to the log's own when known
+    pub fn record_detection(&mut self, detection: &Detection, block_timestamp: Option<u64>) {
+        let block_time = detection
+            .position
+            .block_timestamp
+            .or(block_timestamp)
+            .and_then(|secs| DateTime::from_timestamp(secs as i64, 0));
+        if let Some(block_time) = block_time {
+            self.record(Stage::BlockToReceive, elapsed(block_time, detection.received_at));
+        }
+        self.record(Stage::Classify, elapsed(detection.received_at, detection.classified_at));
+        if detection.caller_path.is_some() {
+            self.record(Stage::Verify, elapsed(detection.classified_at, detection.decided_at));
+        }
+    }
+
+    pub fn record_swap(&mut self, detection: &Detection, swap: &SwapTimings) {
+        self.record(Stage::Queue, elapsed(detection.decided_at, swap.started_at));
+        self.record(Stage::Checks, elapsed(swap.started_at, swap.checked_at));
+        self.record(Stage::Sign, elapsed(swap.checked_at, swap.signed_at));
+        self.record(Stage::Broadcast, elapsed(swap.signed_at, swap.broadcast_at));
+        self.record(Stage::Inclusion, elapsed(swap.broadcast_at, swap.confirmed_at));
+        self.record(Stage::ReceiveToBroadcast, elapsed(detection.received_at, swap.broadcast_at));
+    }
+
+    // One line per stage that has samples, in pipeline order
+    pub fn summary(&self) -> Vec<String> {
+        self.stages
+            .iter()
+            .filter(|(_, histogram)| histogram.count() > 0)
+            .map(|(stage, histogram)| format!("{}: {}", stage, histogram))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_percentiles_use_bucket_bounds() {
+        let mut histogram = Histogram::default();
+        for ms in [3, 4, 4, 8, 15, 15, 40, 90, 450, 45_000] {
+            histogram.record(Duration::from_millis(ms));
+        }
+
+        assert_eq!(histogram.count(), 10);
+        assert_eq!(histogram.percentile(50.0), Duration::from_millis(20));
+        assert_eq!(histogram.percentile(90.0), Duration::from_millis(500));
+        // Overflow bucket reports the observed max
+        assert_eq!(histogram.percentile(99.0), Duration::from_millis(45_000));
+        assert_eq!(histogram.mean(), Duration::from_micros(4_562_900));
+    }
+}
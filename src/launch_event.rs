@@ -0,0 +1,213 @@
+use anyhow::{Result, anyhow};
+use ethers::abi::RawLog;
+use ethers::abi::AbiDecode;
+use ethers::contract::{abigen, EthEvent};
+use ethers::types::{Address, Bytes, H256, U256};
+use serde::Serialize;
+use serde_json::Value;
+
+// Agent factory ABI: the launch event (TARGET_TOPIC is the keccak of its signature)
+// and the entrypoints that emit it, used to recognise launches while still pending
+abigen!(
+    AgentFactory,
+    r#"[
+        event NewPersona(uint256 virtualId, address token, address dao, address tba, address veToken, address lp)
+        function executeApplication(uint256 id, bool canStake)
+        function executeBondingCurveApplication(uint256 id, uint256 totalSupply, uint256 lpSupply, address vault) returns (address)
+    ]"#
+);
+
+// Typed launch event decoded from a TARGET_TOPIC log.
+// The event carries no creator field - the creator is the tx sender and is
+// resolved by verify_caller when none of the emitted addresses classify it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LaunchEvent {
+    pub virtual_id: U256,
+    pub token: Address,
+    pub dao: Address,
+    pub tba: Address,
+    pub ve_token: Address,
+    pub pair: Address,
+}
+
+impl LaunchEvent {
+    // Topic0 of the event this decoder understands
+    pub fn topic() -> H256 {
+        NewPersonaFilter::signature()
+    }
+
+    // Decode from raw topics and data
+    pub fn decode(topics: Vec<H256>, data: Vec<u8>) -> Result<Self> {
+        let event = NewPersonaFilter::decode_log(&RawLog { topics, data })
+            .map_err(|e| anyhow!("Failed to decode launch event: {}", e))?;
+
+        if event.token == Address::zero() {
+            return Err(anyhow!("Launch event has zero token address"));
+        }
+
+        Ok(Self {
+            virtual_id: event.virtual_id,
+            token: event.token,
+            dao: event.dao,
+            tba: event.tba,
+            ve_token: event.ve_token,
+            pair: event.lp,
+        })
+    }
+
+    // Decode from a JSON-RPC log object (subscription result or eth_getLogs entry)
+    pub fn from_log(log: &Value) -> Result<Self> {
+        let topics = log["topics"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Missing log topics"))?
+            .iter()
+            .map(|t| {
+                t.as_str()
+                    .ok_or_else(|| anyhow!("Invalid log topic"))?
+                    .parse::<H256>()
+                    .map_err(|e| anyhow!("Invalid log topic: {}", e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let data = log["data"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing log data"))?
+            .parse::<Bytes>()
+            .map_err(|e| anyhow!("Invalid log data: {}", e))?;
+
+        Self::decode(topics, data.to_vec())
+    }
+
+    // Non-token addresses emitted with the launch, in ABI order
+    pub fn participants(&self) -> [Address; 4] {
+        [self.dao, self.tba, self.ve_token, self.pair]
+    }
+}
+
+// Factory call that will emit a launch event once mined.
+// application_id becomes the event's virtual_id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LaunchCall {
+    pub function: &'static str,
+    pub application_id: U256,
+}
+
+impl LaunchCall {
+    // Decode tx calldata; None for any factory call that does not launch a token
+    pub fn decode(input: &[u8]) -> Option<Self> {
+        match AgentFactoryCalls::decode(input).ok()? {
+            AgentFactoryCalls::ExecuteApplication(call) => Some(Self {
+                function: "executeApplication",
+                application_id: call.id,
+            }),
+            AgentFactoryCalls::ExecuteBondingCurveApplication(call) => Some(Self {
+                function: "executeBondingCurveApplication",
+                application_id: call.id,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{encode, Token};
+    use proptest::prelude::*;
+
+    fn addr(byte: u8) -> Address {
+        Address::repeat_byte(byte)
+    }
+
+    #[test]
+    fn test_signature_matches_target_topic() {
+        assert_eq!(
+            format!("{:?}", NewPersonaFilter::signature()),
+            "0xf9d151d23a5253296eb20ab40959cf48828ea2732d337416716e302ed83ca658"
+        );
+    }
+
+    #[test]
+    fn test_decode_new_persona() {
+        let data = encode(&[
+            Token::Uint(U256::from(42)),
+            Token::Address(addr(0x11)),
+            Token::Address(addr(0x22)),
+            Token::Address(addr(0x33)),
+            Token::Address(addr(0x44)),
+            Token::Address(addr(0x55)),
+        ]);
+
+        let event = LaunchEvent::decode(vec![NewPersonaFilter::signature()], data).unwrap();
+        assert_eq!(event.virtual_id, U256::from(42));
+        assert_eq!(event.token, addr(0x11));
+        assert_eq!(event.pair, addr(0x55));
+        assert_eq!(event.participants(), [addr(0x22), addr(0x33), addr(0x44), addr(0x55)]);
+    }
+
+    #[test]
+    fn test_decode_rejects_wrong_topic_and_short_data() {
+        let data = encode(&[Token::Uint(U256::one()), Token::Address(addr(0x11))]);
+        assert!(LaunchEvent::decode(vec![NewPersonaFilter::signature()], data.clone()).is_err());
+        assert!(LaunchEvent::decode(vec![H256::zero()], data).is_err());
+    }
+
+    #[test]
+    fn test_decode_launch_calls() {
+        use ethers::abi::AbiEncode;
+
+        let input = ExecuteApplicationCall { id: U256::from(7), can_stake: true }.encode();
+        let call = LaunchCall::decode(&input).unwrap();
+        assert_eq!(call.function, "executeApplication");
+        assert_eq!(call.application_id, U256::from(7));
+
+        let input = ExecuteBondingCurveApplicationCall {
+            id: U256::from(9),
+            total_supply: U256::exp10(27),
+            lp_supply: U256::exp10(26),
+            vault: addr(0x66),
+        }
+        .encode();
+        assert_eq!(LaunchCall::decode(&input).unwrap().application_id, U256::from(9));
+
+        assert!(LaunchCall::decode(&[0xde, 0xad, 0xbe, 0xef]).is_none());
+        assert!(LaunchCall::decode(&[]).is_none());
+    }
+
+    fn new_persona(virtual_id: [u8; 32], addresses: &[[u8; 20]]) -> Vec<u8> {
+        let mut tokens = vec![Token::Uint(U256::from_big_endian(&virtual_id))];
+        tokens.extend(addresses.iter().map(|a| Token::Address(Address::from(*a))));
+        encode(&tokens)
+    }
+
+    proptest! {
+        // Hex case in topics and data never changes the decoded event
+        #[test]
+        fn prop_decode_ignores_hex_case(
+            virtual_id in any::<[u8; 32]>(),
+            addresses in proptest::collection::vec(any::<[u8; 20]>(), 5),
+        ) {
+            prop_assume!(addresses[0] != [0; 20]);
+            let data = Bytes::from(new_persona(virtual_id, &addresses)).to_string();
+            let topic = format!("{:?}", LaunchEvent::topic());
+            let upper = |hex: &str| format!("0x{}", hex[2..].to_uppercase());
+
+            let lower = LaunchEvent::from_log(&serde_json::json!({"topics": [topic], "data": data})).unwrap();
+            let mixed = LaunchEvent::from_log(&serde_json::json!({"topics": [upper(&topic)], "data": upper(&data)})).unwrap();
+            prop_assert_eq!(&lower, &mixed);
+            prop_assert_eq!(lower.token, Address::from(addresses[0]));
+            prop_assert_eq!(lower.participants().to_vec(), addresses[1..].iter().map(|a| Address::from(*a)).collect::<Vec<_>>());
+        }
+
+        // Anything short of the six ABI words, or an odd number of hex digits, is an error
+        #[test]
+        fn prop_short_or_odd_data_is_rejected(
+            virtual_id in any::<[u8; 32]>(),
+            addresses in proptest::collection::vec(any::<[u8; 20]>(), 5),
+            digits in 0usize..384,
+        ) {
+            let data = Bytes::from(new_persona(virtual_id, &addresses)).to_string();
+            let log = serde_json::json!({"topics": [LaunchEvent::topic()], "data": &data[..2 + digits]});
+            prop_assert!(LaunchEvent::from_log(&log).is_err());
+        }
+    }
+}
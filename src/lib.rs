@@ -0,0 +1,21 @@
+// Shared core of the sniper: every binary in src/ and src/bin/ is a thin front-end over these modules
+
+pub mod bytecode;
+pub mod cache;
+pub mod config;
+pub mod detection;
+pub mod detector;
+pub mod execution;
+pub mod honeypot;
+pub mod latency;
+pub mod launch_event;
+pub mod liquidity;
+pub mod metadata;
+#[cfg(test)]
+mod mock_node;
+pub mod race;
+pub mod recording;
+pub mod rpc;
+pub mod scan;
+pub mod strategy;
+pub mod uniswap;
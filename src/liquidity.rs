@@ -0,0 +1,213 @@
+use anyhow::{Result, anyhow};
+use ethers::prelude::*;
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+// Uniswap V2 factory: pair lookup and creation
+abigen!(
+    UniswapV2Factory,
+    r#"[
+        function getPair(address tokenA, address tokenB) external view returns (address pair)
+        event PairCreated(address indexed token0, address indexed token1, address pair, uint256 index)
+    ]"#
+);
+
+// Uniswap V2 pair: reserves and their updates
+abigen!(
+    UniswapV2Pair,
+    r#"[
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+        event Sync(uint112 reserve0, uint112 reserve1)
+    ]"#
+);
+
+// Pool state at the moment the buy was released
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Liquidity {
+    pub pair: Address,
+    pub reserve_in: U256,
+    pub reserve_out: U256,
+    pub waited: Duration,
+}
+
+impl fmt::Display for Liquidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pair {:?}, reserves {} in / {} out (waited {:?})",
+            self.pair, self.reserve_in, self.reserve_out, self.waited)
+    }
+}
+
+// Pair reserves reordered as (input side, token side); pairs sort their tokens by address
+fn oriented(input_token: Address, token: Address, reserve0: U256, reserve1: U256) -> (U256, U256) {
+    if input_token < token {
+        (reserve0, reserve1)
+    } else {
+        (reserve1, reserve0)
+    }
+}
+
+// Both sides funded and the input side at or above the minimum
+fn is_ready(reserve_in: U256, reserve_out: U256, min_reserve_in: U256) -> bool {
+    !reserve_in.is_zero() && !reserve_out.is_zero() && reserve_in >= min_reserve_in
+}
+
+// Resolve the [input_token, token] pair on `factory` and return once its input-side reserve reaches
+// `min_reserve_in`. A funded pool costs two reads. Otherwise it subscribes to PairCreated/Sync
+// and reads once more, so an update between the first read and the subscription is not missed.
+pub async fn wait_for_liquidity<M: Middleware + 'static>(
+    client: Arc<M>,
+    factory: Address,
+    input_token: Address,
+    token: Address,
+    min_reserve_in: U256,
+    timeout: Duration,
+) -> Result<Liquidity>
+where
+    M::Provider: PubsubClient,
+{
+    let started = Instant::now();
+    let wait = async {
+        let factory = UniswapV2Factory::new(factory, client.clone());
+        let get_pair = || async {
+            factory
+                .get_pair(input_token, token)
+                .call()
+                .await
+                .map_err(|e| anyhow!("Failed to look up pair: {}", e))
+        };
+
+        let mut pair = get_pair().await?;
+        if pair.is_zero() {
+            let (token0, token1) = if input_token < token { (input_token, token) } else { (token, input_token) };
+            let created = factory
+                .event::<PairCreatedFilter>()
+                .topic1(token0)
+                .topic2(token1)
+                .filter;
+            let mut created = client
+                .subscribe_logs(&created)
+                .await
+                .map_err(|e| anyhow!("Failed to subscribe to PairCreated: {}", e))?;
+
+            pair = get_pair().await?;
+            if pair.is_zero() {
+                info!("💧 No pair for {:?} yet - waiting for PairCreated", token);
+            }
+            while pair.is_zero() {
+                let log = created.next().await.ok_or_else(|| anyhow!("PairCreated subscription ended"))?;
+                pair = factory
+                    .decode_event::<PairCreatedFilter>("PairCreated", log.topics, log.data)
+                    .map_err(|e| anyhow!("Invalid PairCreated log: {}", e))?
+                    .pair;
+            }
+        }
+
+        let pool = UniswapV2Pair::new(pair, client.clone());
+        let get_reserves = || async {
+            let (reserve0, reserve1, _) = pool
+                .get_reserves()
+                .call()
+                .await
+                .map_err(|e| anyhow!("Failed to read reserves of {:?}: {}", pair, e))?;
+            anyhow::Ok(oriented(input_token, token, reserve0.into(), reserve1.into()))
+        };
+
+        let (mut reserve_in, mut reserve_out) = get_reserves().await?;
+        if !is_ready(reserve_in, reserve_out, min_reserve_in) {
+            let synced = pool.event::<SyncFilter>().filter;
+            let mut synced = client
+                .subscribe_logs(&synced)
+                .await
+                .map_err(|e| anyhow!("Failed to subscribe to Sync: {}", e))?;
+
+            (reserve_in, reserve_out) = get_reserves().await?;
+            if !is_ready(reserve_in, reserve_out, min_reserve_in) {
+                info!("💧 Pair {:?} has {} input reserve (need {}) - waiting for Sync", pair, reserve_in, min_reserve_in);
+            }
+            while !is_ready(reserve_in, reserve_out, min_reserve_in) {
+                let log = synced.next().await.ok_or_else(|| anyhow!("Sync subscription ended"))?;
+                let sync = pool
+                    .decode_event::<SyncFilter>("Sync", log.topics, log.data)
+                    .map_err(|e| anyhow!("Invalid Sync log: {}", e))?;
+                (reserve_in, reserve_out) = oriented(input_token, token, sync.reserve_0.into(), sync.reserve_1.into());
+            }
+        }
+
+        anyhow::Ok(Liquidity {
+            pair,
+            reserve_in,
+            reserve_out,
+            waited: started.elapsed(),
+        })
+    };
+
+    tokio::time::timeout(timeout, wait)
+        .await
+        .map_err(|_| anyhow!("no liquidity for {:?} within {:?}", token, timeout))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_node::{output, MockNode};
+    use ethers::abi::Token;
+    use ethers::contract::EthCall;
+
+    // input_token sorts first, so reserve0 is the input side
+    const INPUT: Address = H160([0x01; 20]);
+    const TOKEN: Address = H160([0x02; 20]);
+    const FACTORY: Address = H160([0xfa; 20]);
+    const PAIR: Address = H160([0x9a; 20]);
+
+    async fn pool_node(reserve_in: u64, reserve_out: u64) -> (MockNode, Arc<Provider<Ws>>) {
+        let node = MockNode::start().await;
+        node.on_call(FACTORY, GetPairCall::selector(), output(&[Token::Address(PAIR)]));
+        node.on_call(PAIR, GetReservesCall::selector(), output(&[Token::Uint(reserve_in.into()), Token::Uint(reserve_out.into()), Token::Uint(0.into())]));
+        let client = Arc::new(Provider::<Ws>::connect(node.url()).await.unwrap());
+        (node, client)
+    }
+
+    #[test]
+    fn test_reserves_are_oriented_and_gated_on_the_input_side() {
+        let (low, high) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+        assert_eq!(oriented(low, high, 5.into(), 7.into()), (5.into(), 7.into()));
+        assert_eq!(oriented(high, low, 5.into(), 7.into()), (7.into(), 5.into()));
+
+        assert!(!is_ready(0.into(), 0.into(), 0.into()));
+        assert!(!is_ready(10.into(), 0.into(), 0.into()));
+        assert!(!is_ready(9.into(), 100.into(), 10.into()));
+        assert!(is_ready(10.into(), 100.into(), 10.into()));
+    }
+
+    #[tokio::test]
+    async fn test_funded_pool_is_read_without_subscribing() {
+        let (node, client) = pool_node(1_000, 5_000).await;
+
+        let liquidity = wait_for_liquidity(client, FACTORY, INPUT, TOKEN, 100.into(), Duration::from_secs(5)).await.unwrap();
+        assert_eq!((liquidity.pair, liquidity.reserve_in, liquidity.reserve_out), (PAIR, 1_000.into(), 5_000.into()));
+        assert_eq!(node.requests("eth_call").len(), 2);
+        assert!(node.requests("eth_subscribe").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_thin_pool_waits_for_sync() {
+        let (node, client) = pool_node(10, 5_000).await;
+        let sync = SyncFilter { reserve_0: 1_000, reserve_1: 4_000 };
+        node.push_log(serde_json::json!({
+            "address": PAIR,
+            "topics": [SyncFilter::signature()],
+            "data": Bytes::from(ethers::abi::encode(&[Token::Uint(sync.reserve_0.into()), Token::Uint(sync.reserve_1.into())])),
+            "blockNumber": "0x10",
+        }));
+
+        let liquidity = wait_for_liquidity(client, FACTORY, INPUT, TOKEN, 100.into(), Duration::from_secs(5)).await.unwrap();
+        assert_eq!((liquidity.reserve_in, liquidity.reserve_out), (1_000.into(), 4_000.into()));
+        // getPair, getReserves, then one re-read after subscribing
+        assert_eq!(node.requests("eth_call").len(), 3);
+        assert_eq!(node.requests("eth_subscribe").len(), 1);
+    }
+}
@@ -0,0 +1,169 @@
+use anyhow::{Result, anyhow};
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::contract::abigen;
+use ethers::types::{Address, Bytes, U256};
+use serde::Serialize;
+use std::fmt;
+
+use crate::rpc::RpcClient;
+
+// Multicall3 is deployed at the same address on every supported chain
+const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+// Multicall3 ABI (aggregate3 only)
+abigen!(
+    Multicall3,
+    r#"[
+        struct Call3 { address target; bool allowFailure; bytes callData; }
+        struct CallResult { bool success; bytes returnData; }
+        function aggregate3(Call3[] calls) external payable returns (CallResult[] returnData)
+    ]"#
+);
+
+// Token views read after a launch; owner() is optional on most tokens
+abigen!(
+    TokenInfo,
+    r#"[
+        function name() external view returns (string)
+        function symbol() external view returns (string)
+        function decimals() external view returns (uint8)
+        function totalSupply() external view returns (uint256)
+        function owner() external view returns (address)
+        function balanceOf(address account) external view returns (uint256)
+    ]"#
+);
+
+// What a freshly launched token says about itself; each field is None if its call failed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TokenMetadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    pub total_supply: Option<U256>,
+    pub owner: Option<Address>,
+    // Token balance of the launch's caller, when one was resolved
+    pub creator_balance: Option<U256>,
+}
+
+impl fmt::Display for TokenMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}), {} decimals, supply {}",
+            self.name.as_deref().unwrap_or("?"),
+            self.symbol.as_deref().unwrap_or("?"),
+            self.decimals.map_or("?".to_string(), |d| d.to_string()),
+            self.total_supply.map_or("?".to_string(), |s| s.to_string()))?;
+        if let Some(owner) = self.owner {
+            write!(f, ", owner {:?}", owner)?;
+        }
+        if let Some(balance) = self.creator_balance {
+            write!(f, ", creator holds {}", balance)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn multicall3_address() -> Address {
+    MULTICALL3_ADDRESS.parse().expect("valid Multicall3 address")
+}
+
+// aggregate3 calldata for every metadata view; balanceOf is only added with a creator
+pub fn metadata_calldata(token: Address, creator: Option<Address>) -> Bytes {
+    let mut calls: Vec<Bytes> = vec![
+        NameCall.encode().into(),
+        SymbolCall.encode().into(),
+        DecimalsCall.encode().into(),
+        TotalSupplyCall.encode().into(),
+        OwnerCall.encode().into(),
+    ];
+    if let Some(account) = creator {
+        calls.push(BalanceOfCall { account }.encode().into());
+    }
+
+    Aggregate3Call {
+        calls: calls
+            .into_iter()
+            .map(|call_data| Call3 { target: token, allow_failure: true, call_data })
+            .collect(),
+    }
+    .encode()
+    .into()
+}
+
+// Decode an aggregate3 result built by metadata_calldata
+pub fn decode_metadata(output: &[u8]) -> Result<TokenMetadata> {
+    let Aggregate3Return { return_data: results } = Aggregate3Return::decode(output)
+        .map_err(|e| anyhow!("Invalid aggregate3 result: {}", e))?;
+
+    // A failed or malformed view just leaves its field empty
+    fn field<T: AbiDecode>(results: &[(bool, Bytes)], index: usize) -> Option<T> {
+        let (_, data) = results.get(index).filter(|(success, _)| *success)?;
+        T::decode(data).ok()
+    }
+
+    Ok(TokenMetadata {
+        name: field::<NameReturn>(&results, 0).map(|r| r.0),
+        symbol: field::<SymbolReturn>(&results, 1).map(|r| r.0),
+        decimals: field::<DecimalsReturn>(&results, 2).map(|r| r.0),
+        total_supply: field::<TotalSupplyReturn>(&results, 3).map(|r| r.0),
+        owner: field::<OwnerReturn>(&results, 4).map(|r| r.0),
+        creator_balance: field::<BalanceOfReturn>(&results, 5).map(|r| r.0),
+    })
+}
+
+// One eth_call for all metadata, pinned to the launch block when known
+pub async fn fetch_metadata(
+    rpc: &RpcClient,
+    token: Address,
+    creator: Option<Address>,
+    block: Option<u64>,
+) -> Result<TokenMetadata> {
+    let block_tag = block.map_or("latest".to_string(), |b| format!("0x{:x}", b));
+    let result = rpc
+        .request("eth_call", serde_json::json!([{
+            "to": multicall3_address(),
+            "data": metadata_calldata(token, creator)
+        }, block_tag]))
+        .await?;
+
+    let output = result
+        .as_str()
+        .ok_or_else(|| anyhow!("Unexpected eth_call result: {}", result))?
+        .parse::<Bytes>()
+        .map_err(|e| anyhow!("Invalid eth_call result: {}", e))?;
+    decode_metadata(&output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(data: impl AbiEncode) -> (bool, Bytes) {
+        (true, data.encode().into())
+    }
+
+    #[test]
+    fn test_decodes_aggregate3_results_and_tolerates_failed_calls() {
+        let calldata = metadata_calldata(Address::repeat_byte(0x11), Some(Address::repeat_byte(0x22)));
+        let Aggregate3Call { calls } = Aggregate3Call::decode(&calldata).unwrap();
+        assert_eq!(calls.len(), 6);
+        assert!(calls.iter().all(|c| c.target == Address::repeat_byte(0x11) && c.allow_failure));
+
+        let output = Aggregate3Return { return_data: vec![
+            ok(NameReturn("Agent".to_string())),
+            ok(SymbolReturn("AGT".to_string())),
+            ok(DecimalsReturn(18)),
+            ok(TotalSupplyReturn(U256::exp10(27))),
+            // No owner() on this token
+            (false, Bytes::new()),
+            ok(BalanceOfReturn(U256::exp10(24))),
+        ] }
+        .encode();
+
+        let metadata = decode_metadata(&output).unwrap();
+        assert_eq!(metadata.symbol.as_deref(), Some("AGT"));
+        assert_eq!(metadata.decimals, Some(18));
+        assert_eq!(metadata.total_supply, Some(U256::exp10(27)));
+        assert_eq!(metadata.owner, None);
+        assert_eq!(metadata.creator_balance, Some(U256::exp10(24)));
+    }
+}
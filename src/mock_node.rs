@@ -0,0 +1,383 @@
+// Test support: an in-process Ethereum JSON-RPC node over WebSocket, driven by scripted fixtures.
+// Both RpcClient and ethers' Provider<Ws> can connect to it, any number of times.
+
+use ethers::abi::Token;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TxHash};
+use ethers::utils::{keccak256, rlp::Rlp};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+use tokio_tungstenite::{accept_async, tungstenite::protocol::Message};
+
+use crate::config::Profile;
+use crate::rpc::RpcError;
+
+// How a scripted method answers one request
+pub enum Reply {
+    Result(Value),
+    // A JSON-RPC error response
+    Error(RpcError),
+    // Never answer, like a stalled node
+    Hang,
+    // Not scripted for these params; use the built-in behaviour
+    Builtin,
+}
+
+type Handler = Box<dyn Fn(&Value) -> Reply + Send>;
+
+struct Subscription {
+    id: String,
+    // First eth_subscribe param: "logs" or "newPendingTransactions"
+    kind: String,
+    filter: Value,
+    outbound: mpsc::UnboundedSender<String>,
+}
+
+// A raw transaction accepted by eth_sendRawTransaction
+#[derive(Debug, Clone)]
+pub struct SentTx {
+    pub hash: TxHash,
+    pub from: Address,
+    pub tx: TypedTransaction,
+}
+
+#[derive(Default)]
+struct State {
+    handlers: HashMap<String, Handler>,
+    // eth_call outputs by (to, selector)
+    calls: HashMap<(Address, [u8; 4]), Bytes>,
+    logs: Vec<Value>,
+    // Mined while no subscriber was connected: served by eth_getLogs, never pushed
+    missed_logs: Vec<Value>,
+    pending_txs: Vec<Value>,
+    subscriptions: Vec<Subscription>,
+    requests: Vec<(String, Value)>,
+    sent: Vec<SentTx>,
+    next_subscription: u64,
+}
+
+pub struct MockNode {
+    url: String,
+    state: Arc<Mutex<State>>,
+    // Bumped to drop every open connection
+    disconnects: watch::Sender<u64>,
+}
+
+const CHAIN_ID: u64 = 8453;
+const BLOCK_NUMBER: u64 = 0x10;
+// Hash of BLOCK_NUMBER
+pub const BLOCK_HASH: TxHash = TxHash::repeat_byte(0xbb);
+
+fn hex(n: u64) -> Value {
+    Value::String(format!("0x{:x}", n))
+}
+
+fn notification(id: &str, result: &Value) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_subscription",
+        "params": {"subscription": id, "result": result}
+    })
+    .to_string()
+}
+
+// A filter field (address or topic) matches when absent/null, equal, or listed
+fn field_matches(wanted: &Value, actual: &Value) -> bool {
+    let same = |w: &Value| w.as_str().zip(actual.as_str()).is_some_and(|(w, a)| w.eq_ignore_ascii_case(a));
+    match wanted {
+        Value::Null => true,
+        Value::Array(options) => options.iter().any(same),
+        wanted => same(wanted),
+    }
+}
+
+fn log_matches(filter: &Value, log: &Value) -> bool {
+    if !field_matches(&filter["address"], &log["address"]) {
+        return false;
+    }
+    let topics = filter["topics"].as_array().cloned().unwrap_or_default();
+    if !topics.iter().enumerate().all(|(i, topic)| field_matches(topic, &log["topics"][i])) {
+        return false;
+    }
+    let block = |v: &Value| v.as_str().and_then(crate::detection::parse_hex_u64);
+    let number = block(&log["blockNumber"]).unwrap_or_default();
+    block(&filter["fromBlock"]).is_none_or(|from| number >= from) && block(&filter["toBlock"]).is_none_or(|to| number <= to)
+}
+
+impl MockNode {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        let (disconnects, _) = watch::channel(0);
+
+        let shared = state.clone();
+        let watcher = disconnects.subscribe();
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let state = shared.clone();
+                let mut disconnect = watcher.clone();
+                disconnect.mark_unchanged();
+                tokio::spawn(async move {
+                    let Ok(ws) = accept_async(tcp).await else {
+                        return;
+                    };
+                    let (mut sink, mut stream) = ws.split();
+                    let (outbound, mut queue) = mpsc::unbounded_channel::<String>();
+                    tokio::spawn(async move {
+                        while let Some(text) = queue.recv().await {
+                            if sink.send(Message::Text(text)).await.is_err() {
+                                break;
+                            }
+                        }
+                    });
+
+                    loop {
+                        let message = tokio::select! {
+                            message = stream.next() => message,
+                            // Dropping both halves closes the socket without a close frame
+                            _ = disconnect.changed() => break,
+                        };
+                        let Some(Ok(Message::Text(text))) = message else {
+                            if matches!(message, Some(Ok(_))) {
+                                continue;
+                            }
+                            break;
+                        };
+                        let Ok(request) = serde_json::from_str::<Value>(&text) else {
+                            continue;
+                        };
+                        Self::serve(&state, &request, &outbound);
+                    }
+                });
+            }
+        });
+
+        Self { url, state, disconnects }
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    // Script a method; takes precedence over the built-in behaviour
+    pub fn on<F>(&self, method: &str, handler: F)
+    where
+        F: Fn(&Value) -> Reply + Send + 'static,
+    {
+        self.state.lock().unwrap().handlers.insert(method.to_string(), Box::new(handler));
+    }
+
+    // Always answer `method` with `result`
+    pub fn respond(&self, method: &str, result: Value) {
+        self.on(method, move |_| Reply::Result(result.clone()));
+    }
+
+    // eth_call to `to` whose calldata starts with `selector` returns `output`
+    pub fn on_call(&self, to: Address, selector: [u8; 4], output: impl Into<Bytes>) {
+        self.state.lock().unwrap().calls.insert((to, selector), output.into());
+    }
+
+    // Served by eth_getLogs and pushed to matching log subscriptions, now and future
+    pub fn push_log(&self, log: Value) {
+        let mut state = self.state.lock().unwrap();
+        for subscription in state.subscriptions.iter().filter(|s| s.kind == "logs" && log_matches(&s.filter, &log)) {
+            let _ = subscription.outbound.send(notification(&subscription.id, &log));
+        }
+        state.logs.push(log);
+    }
+
+    // Served by eth_getLogs only, like a log mined while the subscriber was away
+    pub fn push_missed_log(&self, log: Value) {
+        self.state.lock().unwrap().missed_logs.push(log);
+    }
+
+    // Drop every open connection and its subscriptions, as a node restart would
+    pub fn disconnect(&self) {
+        self.state.lock().unwrap().subscriptions.clear();
+        self.disconnects.send_modify(|n| *n += 1);
+    }
+
+    // Pushed to newPendingTransactions subscriptions, now and future
+    pub fn push_pending_tx(&self, tx: Value) {
+        let mut state = self.state.lock().unwrap();
+        for subscription in state.subscriptions.iter().filter(|s| s.kind == "newPendingTransactions") {
+            let _ = subscription.outbound.send(notification(&subscription.id, &tx));
+        }
+        state.pending_txs.push(tx);
+    }
+
+    // Params of every request for `method` received so far
+    pub fn requests(&self, method: &str) -> Vec<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .requests
+            .iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+
+    pub fn sent_transactions(&self) -> Vec<SentTx> {
+        self.state.lock().unwrap().sent.clone()
+    }
+
+    fn serve(state: &Mutex<State>, request: &Value, outbound: &mpsc::UnboundedSender<String>) {
+        let method = request["method"].as_str().unwrap_or_default();
+        let params = &request["params"];
+        let mut state = state.lock().unwrap();
+        state.requests.push((method.to_string(), params.clone()));
+
+        let reply = match state.handlers.get(method).map(|handler| handler(params)) {
+            Some(Reply::Result(result)) => Ok(result),
+            Some(Reply::Error(e)) => Err(e),
+            Some(Reply::Hang) => return,
+            Some(Reply::Builtin) | None => Self::builtin(&mut state, method, params, outbound),
+        };
+        let message = match reply {
+            Ok(result) => serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+            Err(e) => serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "error": {"code": e.code, "message": e.message}}),
+        };
+        let _ = outbound.send(message.to_string());
+
+        // Backlog after the eth_subscribe reply, so the client knows the id first
+        if method == "eth_subscribe" {
+            if let Some(subscription) = state.subscriptions.last() {
+                let backlog = match subscription.kind.as_str() {
+                    "logs" => state.logs.iter().filter(|log| log_matches(&subscription.filter, log)).collect::<Vec<_>>(),
+                    _ => state.pending_txs.iter().collect(),
+                };
+                for item in backlog {
+                    let _ = outbound.send(notification(&subscription.id, item));
+                }
+            }
+        }
+    }
+
+    fn builtin(state: &mut State, method: &str, params: &Value, outbound: &mpsc::UnboundedSender<String>) -> Result<Value, RpcError> {
+        let error = |code, message: &str| RpcError { code, message: message.to_string() };
+        match method {
+            "eth_chainId" | "net_version" => Ok(hex(CHAIN_ID)),
+            "eth_blockNumber" => Ok(hex(BLOCK_NUMBER)),
+            "eth_getTransactionCount" => Ok(hex(0)),
+            "eth_gasPrice" | "eth_maxPriorityFeePerGas" => Ok(hex(1_000_000_000)),
+            "eth_estimateGas" => Ok(hex(200_000)),
+            "eth_getCode" => Ok(Value::String("0x".to_string())),
+            "eth_subscribe" => {
+                state.next_subscription += 1;
+                let id = format!("0x{:x}", state.next_subscription);
+                state.subscriptions.push(Subscription {
+                    id: id.clone(),
+                    kind: params[0].as_str().unwrap_or_default().to_string(),
+                    filter: params[1].clone(),
+                    outbound: outbound.clone(),
+                });
+                Ok(Value::String(id))
+            }
+            "eth_unsubscribe" => {
+                state.subscriptions.retain(|s| Some(s.id.as_str()) != params[0].as_str());
+                Ok(Value::Bool(true))
+            }
+            "eth_getLogs" => Ok(Value::Array(
+                state.logs.iter().chain(&state.missed_logs).filter(|log| log_matches(&params[0], log)).cloned().collect(),
+            )),
+            "eth_call" => {
+                let call = &params[0];
+                let to = call["to"].as_str().and_then(|to| to.parse::<Address>().ok()).unwrap_or_default();
+                let data = call["data"]
+                    .as_str()
+                    .or(call["input"].as_str())
+                    .and_then(|data| data.parse::<Bytes>().ok())
+                    .unwrap_or_default();
+                let selector: [u8; 4] = data.get(..4).and_then(|s| s.try_into().ok()).unwrap_or_default();
+                state
+                    .calls
+                    .get(&(to, selector))
+                    .map(|output| Value::String(output.to_string()))
+                    .ok_or_else(|| error(3, "execution reverted"))
+            }
+            "eth_sendRawTransaction" => {
+                let raw = params[0]
+                    .as_str()
+                    .and_then(|raw| raw.parse::<Bytes>().ok())
+                    .ok_or_else(|| error(-32602, "invalid raw transaction"))?;
+                let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw))
+                    .map_err(|e| error(-32602, &e.to_string()))?;
+                let from = signature.recover(tx.sighash()).map_err(|e| error(-32602, &e.to_string()))?;
+                let hash = TxHash::from(keccak256(&raw));
+                state.sent.push(SentTx { hash, from, tx });
+                Ok(serde_json::json!(hash))
+            }
+            // Sent transactions are mined immediately, in BLOCK_NUMBER
+            "eth_getTransactionByHash" | "eth_getTransactionReceipt" => {
+                let hash = params[0].as_str().and_then(|h| h.parse::<TxHash>().ok());
+                let Some(sent) = state.sent.iter().find(|sent| Some(sent.hash) == hash) else {
+                    return Ok(Value::Null);
+                };
+                let mined = serde_json::json!({
+                    "blockHash": BLOCK_HASH,
+                    "blockNumber": hex(BLOCK_NUMBER),
+                    "transactionIndex": "0x0",
+                    "from": sent.from,
+                    "to": sent.tx.to_addr(),
+                });
+                let mut result = mined.as_object().cloned().unwrap_or_default();
+                let extra = if method == "eth_getTransactionByHash" {
+                    serde_json::json!({
+                        "hash": sent.hash,
+                        "nonce": sent.tx.nonce().copied().unwrap_or_default(),
+                        "value": sent.tx.value().copied().unwrap_or_default(),
+                        "gas": sent.tx.gas().copied().unwrap_or_default(),
+                        "input": sent.tx.data().cloned().unwrap_or_default(),
+                        "v": "0x0", "r": "0x0", "s": "0x0"
+                    })
+                } else {
+                    serde_json::json!({
+                        "transactionHash": sent.hash,
+                        "cumulativeGasUsed": hex(150_000),
+                        "gasUsed": hex(150_000),
+                        "effectiveGasPrice": hex(1_000_000_000),
+                        "logs": [],
+                        "logsBloom": Bytes::from(vec![0u8; 256]),
+                        "status": "0x1"
+                    })
+                };
+                result.extend(extra.as_object().cloned().unwrap_or_default());
+                Ok(Value::Object(result))
+            }
+            _ => Err(error(-32601, &format!("the method {} does not exist/is not available", method))),
+        }
+    }
+}
+
+// NewPersona log in block 0x10 (BLOCK_HASH) for `token` with the given dao address, as the node delivers it
+pub fn launch_log(profile: &Profile, tx: u8, token: Address, dao: Address) -> Value {
+    let data = ethers::abi::encode(&[
+        Token::Uint(7.into()),
+        Token::Address(token),
+        Token::Address(dao),
+        Token::Address(Address::repeat_byte(0x33)),
+        Token::Address(Address::repeat_byte(0x44)),
+        Token::Address(Address::repeat_byte(0x55)),
+    ]);
+    serde_json::json!({
+        "address": profile.deployer,
+        "topics": [profile.topic],
+        "data": Bytes::from(data),
+        "blockNumber": hex(BLOCK_NUMBER),
+        "blockHash": BLOCK_HASH,
+        "transactionHash": TxHash::repeat_byte(tx),
+        "logIndex": "0x0",
+        "removed": false
+    })
+}
+
+// ABI-encoded eth_call output, e.g. `output(&[Token::Address(pair)])`
+pub fn output(tokens: &[Token]) -> Bytes {
+    ethers::abi::encode(tokens).into()
+}
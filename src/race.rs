@@ -0,0 +1,167 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// Arrivals remembered for dedupe and lag measurement; the oldest is dropped first
+const MAX_TRACKED_ARRIVALS: usize = 1000;
+
+// How one endpoint fared against the others
+#[derive(Debug, Clone, Default)]
+pub struct EndpointStats {
+    pub endpoint: String,
+    // Distinct notifications delivered
+    pub seen: u64,
+    // Notifications this endpoint delivered first
+    pub wins: u64,
+    // Time behind the winner, summed over the notifications it lost
+    pub total_lag: Duration,
+    pub max_lag: Duration,
+}
+
+impl EndpointStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.seen == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.seen as f64
+    }
+
+    pub fn mean_lag(&self) -> Duration {
+        let lost = self.seen - self.wins;
+        if lost == 0 {
+            return Duration::ZERO;
+        }
+        self.total_lag / lost as u32
+    }
+}
+
+impl fmt::Display for EndpointStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: won {}/{} ({:.0}%), mean lag {:?}, max lag {:?}",
+            self.endpoint,
+            self.wins,
+            self.seen,
+            self.win_rate() * 100.0,
+            self.mean_lag(),
+            self.max_lag)
+    }
+}
+
+struct Arrival {
+    first_at: Instant,
+    // Bit per endpoint that has delivered this notification
+    delivered: u64,
+}
+
+// First-seen bookkeeping for the same subscription raced over several endpoints
+pub struct EndpointRace {
+    stats: Vec<EndpointStats>,
+    arrivals: HashMap<String, Arrival>,
+    // Keys in first-arrival order, for evicting the oldest
+    order: VecDeque<String>,
+}
+
+impl EndpointRace {
+    pub const MAX_ENDPOINTS: usize = 64;
+
+    pub fn new(endpoints: &[String]) -> Self {
+        assert!(endpoints.len() <= Self::MAX_ENDPOINTS, "at most {} endpoints can be raced", Self::MAX_ENDPOINTS);
+        Self {
+            stats: endpoints
+                .iter()
+                .map(|url| EndpointStats {
+                    endpoint: redact(url),
+                    ..Default::default()
+                })
+                .collect(),
+            arrivals: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    // Record that `endpoint` delivered `key` at `at`; true only for the first delivery across endpoints
+    pub fn observe(&mut self, endpoint: usize, key: &str, at: Instant) -> bool {
+        let bit = 1u64 << endpoint;
+
+        if let Some(arrival) = self.arrivals.get_mut(key) {
+            if arrival.delivered & bit == 0 {
+                arrival.delivered |= bit;
+                let lag = at.saturating_duration_since(arrival.first_at);
+                let stats = &mut self.stats[endpoint];
+                stats.seen += 1;
+                stats.total_lag += lag;
+                stats.max_lag = stats.max_lag.max(lag);
+            }
+            return false;
+        }
+
+        // One at a time, so recent keys still catch their late copies
+        while self.arrivals.len() >= MAX_TRACKED_ARRIVALS {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.arrivals.remove(&oldest);
+        }
+        self.arrivals.insert(key.to_string(), Arrival { first_at: at, delivered: bit });
+        self.order.push_back(key.to_string());
+
+        let stats = &mut self.stats[endpoint];
+        stats.seen += 1;
+        stats.wins += 1;
+        true
+    }
+
+    pub fn stats(&self) -> Vec<EndpointStats> {
+        self.stats.clone()
+    }
+}
+
+// Scheme and host only - paths and queries usually carry API keys
+fn redact(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let host = rest.split(['/', '?']).next().unwrap_or_default();
+    if scheme.is_empty() {
+        host.to_string()
+    } else {
+        format!("{}://{}", scheme, host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_arrival_wins_and_lag_is_recorded() {
+        let mut race = EndpointRace::new(&["wss://a.example/v2/key".to_string(), "wss://b.example".to_string()]);
+        let start = Instant::now();
+
+        assert!(race.observe(1, "0xaa:0", start));
+        assert!(!race.observe(0, "0xaa:0", start + Duration::from_millis(40)));
+        // Repeats from the same endpoint are neither wins nor extra lag samples
+        assert!(!race.observe(0, "0xaa:0", start + Duration::from_millis(90)));
+        assert!(race.observe(0, "0xbb:1", start + Duration::from_millis(100)));
+
+        let stats = race.stats();
+        assert_eq!(stats[0].endpoint, "wss://a.example");
+        assert_eq!((stats[0].wins, stats[0].seen), (1, 2));
+        assert_eq!(stats[0].mean_lag(), Duration::from_millis(40));
+        assert_eq!((stats[1].wins, stats[1].seen), (1, 1));
+        assert_eq!(stats[1].win_rate(), 1.0);
+        assert_eq!(stats[1].mean_lag(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_full_table_evicts_the_oldest_arrival_only() {
+        let mut race = EndpointRace::new(&["wss://a.example".to_string(), "wss://b.example".to_string()]);
+        let start = Instant::now();
+        for i in 0..=MAX_TRACKED_ARRIVALS {
+            assert!(race.observe(0, &format!("0x{:x}:0", i), start));
+        }
+
+        // The newest keys still dedupe the slower endpoint's copies; only key 0 was forgotten
+        assert!(!race.observe(1, &format!("0x{:x}:0", MAX_TRACKED_ARRIVALS), start));
+        assert!(!race.observe(1, "0x1:0", start));
+        assert!(race.observe(1, "0x0:0", start));
+    }
+}
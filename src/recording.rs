@@ -0,0 +1,227 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use tracing::error;
+
+use crate::rpc::RpcError;
+
+// One inbound WebSocket message, as written to the JSONL capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub at: DateTime<Utc>,
+    pub endpoint: usize,
+    // {"method", "params"} a response answers; absent on subscription notifications
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    pub message: Value,
+}
+
+// Appends records to a JSONL file from a background thread, so the socket reader never
+// blocks on disk. Clones share the file.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    sender: mpsc::Sender<Record>,
+    endpoint: usize,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| anyhow!("Failed to open recording {}: {}", path.display(), e))?;
+        let (sender, receiver) = mpsc::channel::<Record>();
+
+        let path = path.display().to_string();
+        std::thread::spawn(move || {
+            let mut writer = BufWriter::new(file);
+            while let Ok(record) = receiver.recv() {
+                // Flush once the burst is written so a crash loses at most the last burst
+                let written = std::iter::once(record)
+                    .chain(receiver.try_iter())
+                    .try_for_each(|record| writeln!(writer, "{}", serde_json::to_string(&record).unwrap_or_default()))
+                    .and_then(|_| writer.flush());
+                if let Err(e) = written {
+                    error!("❌ Failed to write recording {}: {}", path, e);
+                    return;
+                }
+            }
+        });
+
+        Ok(Self { sender, endpoint: 0 })
+    }
+
+    // Same file, records tagged with another endpoint index
+    pub fn for_endpoint(&self, endpoint: usize) -> Self {
+        Self { sender: self.sender.clone(), endpoint }
+    }
+
+    pub fn record(&self, request: Option<Value>, message: &Value) {
+        let _ = self.sender.send(Record {
+            at: Utc::now(),
+            endpoint: self.endpoint,
+            request,
+            message: message.clone(),
+        });
+    }
+}
+
+// A recorded subscription notification
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub at: DateTime<Utc>,
+    pub endpoint: usize,
+    // First eth_subscribe param, e.g. "logs" or "newPendingTransactions"
+    pub kind: String,
+    // params.result of the notification
+    pub result: Value,
+}
+
+// A loaded capture: notifications in arrival order and responses by request
+#[derive(Debug)]
+pub struct Recording {
+    notifications: Vec<Notification>,
+    // Response messages per method+params; repeated requests get them in recorded order
+    responses: Mutex<HashMap<String, VecDeque<Value>>>,
+}
+
+fn request_key(method: &str, params: &Value) -> String {
+    format!("{} {}", method, params)
+}
+
+impl Recording {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| anyhow!("Failed to open recording {}: {}", path.display(), e))?;
+        let records = BufReader::new(file)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+            .map(|(number, line)| {
+                let line = line.map_err(|e| anyhow!("Failed to read recording {}: {}", path.display(), e))?;
+                serde_json::from_str(&line)
+                    .map_err(|e| anyhow!("Invalid record on line {} of {}: {}", number + 1, path.display(), e))
+            })
+            .collect::<Result<Vec<Record>>>()?;
+        Ok(Self::from_records(records))
+    }
+
+    pub fn from_records(records: impl IntoIterator<Item = Record>) -> Self {
+        let mut subscriptions: HashMap<(usize, String), String> = HashMap::new();
+        let mut responses: HashMap<String, VecDeque<Value>> = HashMap::new();
+        let mut notifications = Vec::new();
+
+        for record in records {
+            if let Some(request) = &record.request {
+                let method = request["method"].as_str().unwrap_or_default();
+                if method == "eth_subscribe" {
+                    if let (Some(id), Some(kind)) = (record.message["result"].as_str(), request["params"][0].as_str()) {
+                        subscriptions.insert((record.endpoint, id.to_string()), kind.to_string());
+                    }
+                }
+                responses
+                    .entry(request_key(method, &request["params"]))
+                    .or_default()
+                    .push_back(record.message);
+                continue;
+            }
+
+            let params = &record.message["params"];
+            let Some(id) = params["subscription"].as_str() else {
+                continue;
+            };
+            // Subscriptions opened before the capture started have no known kind
+            let Some(kind) = subscriptions.get(&(record.endpoint, id.to_string())) else {
+                continue;
+            };
+            notifications.push(Notification {
+                at: record.at,
+                endpoint: record.endpoint,
+                kind: kind.clone(),
+                result: params["result"].clone(),
+            });
+        }
+
+        Self {
+            notifications,
+            responses: Mutex::new(responses),
+        }
+    }
+
+    pub fn notifications(&self) -> &[Notification] {
+        &self.notifications
+    }
+
+    // Recorded answer to a request, decoded like a live response. The last answer
+    // repeats once the recorded ones run out, so extra retries see the final state.
+    pub fn respond(&self, method: &str, params: &Value) -> Result<Value> {
+        let mut responses = self.responses.lock().expect("recording lock poisoned");
+        let queue = responses
+            .get_mut(&request_key(method, params))
+            .ok_or_else(|| anyhow!("No recorded response to {} {}", method, params))?;
+        let message = if queue.len() > 1 {
+            queue.pop_front().unwrap_or_default()
+        } else {
+            queue.front().cloned().unwrap_or_default()
+        };
+
+        match message.get("error") {
+            Some(error) => Err(anyhow::Error::new(RpcError::from_json(error))),
+            None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trips_through_jsonl() {
+        let path = std::env::temp_dir().join(format!("recording-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = Recorder::create(&path).unwrap().for_endpoint(1);
+        let subscribe = serde_json::json!({"method": "eth_subscribe", "params": ["logs", {}]});
+        let lookup = serde_json::json!({"method": "eth_getTransactionByHash", "params": ["0xab"]});
+        recorder.record(Some(subscribe), &serde_json::json!({"id": 1, "result": "0xsub"}));
+        recorder.record(None, &serde_json::json!({"params": {"subscription": "0xsub", "result": {"logIndex": "0x0"}}}));
+        recorder.record(None, &serde_json::json!({"params": {"subscription": "0xother", "result": {}}}));
+        recorder.record(Some(lookup.clone()), &serde_json::json!({"id": 2, "result": null}));
+        recorder.record(Some(lookup), &serde_json::json!({"id": 3, "result": {"from": "0x01"}}));
+        recorder.record(
+            Some(serde_json::json!({"method": "debug_traceTransaction", "params": ["0xab"]})),
+            &serde_json::json!({"id": 4, "error": {"code": -32601, "message": "no debug"}}),
+        );
+        drop(recorder);
+
+        // The writer thread drains asynchronously
+        for _ in 0..100 {
+            if std::fs::read_to_string(&path).unwrap_or_default().lines().count() == 6 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let recording = Recording::from_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let notifications = recording.notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!((notifications[0].endpoint, notifications[0].kind.as_str()), (1, "logs"));
+        assert_eq!(notifications[0].result["logIndex"], "0x0");
+
+        let params = serde_json::json!(["0xab"]);
+        assert!(recording.respond("eth_getTransactionByHash", &params).unwrap().is_null());
+        assert_eq!(recording.respond("eth_getTransactionByHash", &params).unwrap()["from"], "0x01");
+        assert_eq!(recording.respond("eth_getTransactionByHash", &params).unwrap()["from"], "0x01");
+        let error = recording.respond("debug_traceTransaction", &params).unwrap_err();
+        assert_eq!(error.downcast_ref::<RpcError>().unwrap().code, -32601);
+        assert!(recording.respond("eth_blockNumber", &serde_json::json!([])).is_err());
+    }
+}
@@ -0,0 +1,338 @@
+use anyhow::{Result, anyhow};
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
+use tracing::{info, error};
+
+use crate::recording::{Recorder, Recording};
+
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+// Error object returned by the node (as opposed to a transport failure)
+#[derive(Debug, Clone)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JSON-RPC error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl RpcError {
+    pub(crate) fn from_json(error: &Value) -> Self {
+        Self {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"].as_str().unwrap_or_default().to_string(),
+        }
+    }
+    
+    // The node doesn't serve this method at all, as opposed to failing this request
+    pub fn is_method_not_found(&self) -> bool {
+        // Providers that don't use -32601: "method not available", "the method ... does not exist"
+        let message = self.message.to_lowercase();
+        self.code == -32601
+            || message.contains("method not available")
+            || (message.contains("method") && message.contains("does not exist"))
+    }
+}
+
+// A request waiting for its response; subscribe requests carry the notification channel
+struct Pending {
+    response: oneshot::Sender<Result<Value>>,
+    notifications: Option<mpsc::UnboundedSender<Value>>,
+    // {"method", "params"}, kept only while recording
+    request: Option<Value>,
+}
+
+// Live eth_subscribe stream; yields `params.result` of each notification
+pub struct Subscription {
+    pub id: String,
+    receiver: mpsc::UnboundedReceiver<Value>,
+}
+
+impl Subscription {
+    // None once the underlying socket has closed
+    pub async fn next(&mut self) -> Option<Value> {
+        self.receiver.recv().await
+    }
+}
+
+enum Transport {
+    Socket {
+        sink: Mutex<WsSink>,
+        pending: Arc<Mutex<HashMap<u64, Pending>>>,
+        closed: Arc<AtomicBool>,
+    },
+    // Answers requests from a capture instead of a node
+    Replay(Arc<Recording>),
+}
+
+// JSON-RPC client multiplexing requests and subscriptions over one WebSocket
+pub struct RpcClient {
+    transport: Transport,
+    next_id: AtomicU64,
+    recorder: Option<Recorder>,
+}
+
+impl RpcClient {
+    pub async fn connect(url: &str) -> Result<Arc<Self>> {
+        Self::connect_recorded(url, None).await
+    }
+
+    // As connect, also writing every inbound message to `recorder`
+    pub async fn connect_recorded(url: &str, recorder: Option<Recorder>) -> Result<Arc<Self>> {
+        let (ws_stream, _) = connect_async(url).await
+            .map_err(|e| anyhow!("Failed to connect to WebSocket: {}", e))?;
+        let (sink, mut stream) = ws_stream.split();
+
+        let pending: Arc<Mutex<HashMap<u64, Pending>>> = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        // Reader task: match responses by id and route notifications by subscription id
+        {
+            let pending = pending.clone();
+            let closed = closed.clone();
+            let recorder = recorder.clone();
+            tokio::spawn(async move {
+                let mut subscriptions: HashMap<String, mpsc::UnboundedSender<Value>> = HashMap::new();
+
+                while let Some(msg) = stream.next().await {
+                    let text = match msg {
+                        Ok(Message::Text(text)) => text,
+                        Ok(Message::Close(_)) => {
+                            info!("🔌 WebSocket connection closed");
+                            break;
+                        }
+                        Ok(_) => continue,
+                        Err(e) => {
+                            error!("❌ WebSocket error: {}", e);
+                            break;
+                        }
+                    };
+
+                    let json = match serde_json::from_str::<Value>(&text) {
+                        Ok(json) => json,
+                        Err(_) => {
+                            error!("❌ Failed to parse WebSocket message as JSON: {}", text);
+                            continue;
+                        }
+                    };
+
+                    if let Some(id) = json.get("id").and_then(|id| id.as_u64()) {
+                        let Some(request) = pending.lock().await.remove(&id) else {
+                            continue;
+                        };
+                        if let Some(recorder) = &recorder {
+                            recorder.record(request.request, &json);
+                        }
+
+                        let result = match json.get("error") {
+                            Some(error) => Err(anyhow::Error::new(RpcError::from_json(error))),
+                            None => Ok(json.get("result").cloned().unwrap_or(Value::Null)),
+                        };
+
+                        // Register before resolving so no notification can race the caller
+                        if let (Ok(Value::String(sub_id)), Some(sender)) = (&result, request.notifications) {
+                            subscriptions.insert(sub_id.clone(), sender);
+                        }
+
+                        let _ = request.response.send(result);
+                        continue;
+                    }
+
+                    if json.get("method").and_then(|m| m.as_str()) == Some("eth_subscription") {
+                        if let Some(recorder) = &recorder {
+                            recorder.record(None, &json);
+                        }
+                        let params = &json["params"];
+                        if let Some(sub_id) = params["subscription"].as_str() {
+                            let delivered = subscriptions
+                                .get(sub_id)
+                                .map(|sender| sender.send(params["result"].clone()).is_ok());
+                            if delivered == Some(false) {
+                                subscriptions.remove(sub_id);
+                            }
+                        }
+                    }
+                }
+
+                // Fail everything still in flight; dropping the senders ends the subscriptions
+                let mut pending = pending.lock().await;
+                closed.store(true, Ordering::SeqCst);
+                for (_, request) in pending.drain() {
+                    let _ = request.response.send(Err(anyhow!("WebSocket connection closed")));
+                }
+            });
+        }
+
+        Ok(Arc::new(Self {
+            transport: Transport::Socket {
+                sink: Mutex::new(sink),
+                pending,
+                closed,
+            },
+            next_id: AtomicU64::new(1),
+            recorder,
+        }))
+    }
+
+    // Offline client answering every request from `recording`; subscriptions are refused,
+    // recorded notifications are fed by TokenDetector::replay instead
+    pub fn replay(recording: Arc<Recording>) -> Arc<Self> {
+        Arc::new(Self {
+            transport: Transport::Replay(recording),
+            next_id: AtomicU64::new(1),
+            recorder: None,
+        })
+    }
+
+    pub fn is_closed(&self) -> bool {
+        match &self.transport {
+            Transport::Socket { closed, .. } => closed.load(Ordering::SeqCst),
+            Transport::Replay(_) => false,
+        }
+    }
+
+    async fn send(&self, method: &str, params: Value, notifications: Option<mpsc::UnboundedSender<Value>>) -> Result<Value> {
+        let (sink, pending) = match &self.transport {
+            Transport::Socket { sink, pending, .. } => (sink, pending),
+            Transport::Replay(_) if notifications.is_some() => {
+                return Err(anyhow::Error::new(RpcError {
+                    code: -32601,
+                    message: "subscriptions are not replayed".to_string(),
+                }));
+            }
+            Transport::Replay(recording) => return recording.respond(method, &params),
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (response, receiver) = oneshot::channel();
+        let request = self.recorder.as_ref().map(|_| serde_json::json!({"method": method, "params": params}));
+        {
+            // Checked after inserting so a concurrent close either drains us or is seen here
+            let mut pending = pending.lock().await;
+            pending.insert(id, Pending { response, notifications, request });
+            if self.is_closed() {
+                pending.remove(&id);
+                return Err(anyhow!("WebSocket connection closed"));
+            }
+        }
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        });
+
+        if let Err(e) = sink.lock().await.send(Message::Text(request.to_string())).await {
+            pending.lock().await.remove(&id);
+            return Err(anyhow!("Failed to send {}: {}", method, e));
+        }
+
+        receiver.await.map_err(|_| anyhow!("WebSocket connection closed"))?
+    }
+
+    // Single request/response call
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.send(method, params, None).await
+    }
+
+    // eth_subscribe with the given params, e.g. ["logs", filter]
+    pub async fn subscribe(&self, params: Value) -> Result<Subscription> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let result = self.send("eth_subscribe", params, Some(sender)).await?;
+        let id = result
+            .as_str()
+            .ok_or_else(|| anyhow!("Unexpected eth_subscribe result: {}", result))?
+            .to_string();
+
+        Ok(Subscription { id, receiver })
+    }
+
+    pub async fn unsubscribe(&self, subscription: &Subscription) -> Result<()> {
+        self.request("eth_unsubscribe", serde_json::json!([subscription.id])).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    // Answers requests in reverse order and pushes one notification after eth_subscribe
+    async fn spawn_node() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            let mut held = Vec::new();
+
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: Value = serde_json::from_str(&text).unwrap();
+                let id = request["id"].clone();
+                match request["method"].as_str().unwrap() {
+                    "eth_subscribe" => {
+                        let reply = serde_json::json!({"jsonrpc": "2.0", "id": id, "result": "0xsub"});
+                        ws.send(Message::Text(reply.to_string())).await.unwrap();
+                        let note = serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "method": "eth_subscription",
+                            "params": {"subscription": "0xsub", "result": {"blockNumber": "0x1"}}
+                        });
+                        ws.send(Message::Text(note.to_string())).await.unwrap();
+                    }
+                    "eth_fail" => {
+                        let reply = serde_json::json!({"jsonrpc": "2.0", "id": id, "error": {"code": -32000, "message": "nope"}});
+                        ws.send(Message::Text(reply.to_string())).await.unwrap();
+                    }
+                    method => {
+                        held.push(serde_json::json!({"jsonrpc": "2.0", "id": id, "result": method}));
+                        if held.len() == 2 {
+                            for reply in held.drain(..).rev() {
+                                ws.send(Message::Text(reply.to_string())).await.unwrap();
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        format!("ws://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_multiplexed_requests_and_subscription() {
+        let client = RpcClient::connect(&spawn_node().await).await.unwrap();
+
+        let (a, b) = tokio::join!(
+            client.request("eth_a", serde_json::json!([])),
+            client.request("eth_b", serde_json::json!([]))
+        );
+        assert_eq!(a.unwrap(), "eth_a");
+        assert_eq!(b.unwrap(), "eth_b");
+
+        let err = client.request("eth_fail", serde_json::json!([])).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<RpcError>().unwrap().code, -32000);
+
+        let mut subscription = client.subscribe(serde_json::json!(["logs", {}])).await.unwrap();
+        assert_eq!(subscription.id, "0xsub");
+        assert_eq!(subscription.next().await.unwrap()["blockNumber"], "0x1");
+    }
+}
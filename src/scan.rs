@@ -0,0 +1,451 @@
+use anyhow::{Result, anyhow};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::detection::parse_hex_u64;
+use crate::rpc::RpcError;
+
+const DEFAULT_CHUNK_SIZE: u64 = 2_000;
+const DEFAULT_CONCURRENCY: usize = 4;
+
+// Provider messages for an eth_getLogs range or result set that is too big
+const TOO_LARGE_PATTERNS: [&str; 5] = [
+    "block range",
+    "range too large",
+    "range is too large",
+    "query returned more than",
+    "response size exceeded",
+];
+
+// Provider messages for throttled requests; the range is fine, just retry it later.
+// Range errors are checked first, so these never hide a too-large range.
+const RATE_LIMIT_PATTERNS: [&str; 4] = [
+    "rate limit",
+    "too many requests",
+    "request limit",
+    "limit exceeded",
+];
+
+// Backoff for a rate-limited chunk, doubling per consecutive refusal
+const RATE_LIMIT_BASE_DELAY_MS: u64 = 200;
+const RATE_LIMIT_MAX_DELAY_MS: u64 = 10_000;
+const MAX_RATE_LIMIT_RETRIES: u32 = 8;
+
+// How historical eth_getLogs scans are split up
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    // Blocks per eth_getLogs request; halved whenever a provider rejects the range
+    pub chunk_size: u64,
+    // Requests in flight at once
+    pub concurrency: usize,
+    // Progress file; an interrupted scan over the same range and filter resumes from it.
+    // Logs found so far are appended next to it, see `Checkpoint::logs_path`.
+    pub checkpoint: Option<PathBuf>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+            checkpoint: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    // SCAN_CHUNK_SIZE, SCAN_CONCURRENCY and SCAN_CHECKPOINT
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            chunk_size: std::env::var("SCAN_CHUNK_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&size| size > 0)
+                .unwrap_or(defaults.chunk_size),
+            concurrency: std::env::var("SCAN_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n| n > 0)
+                .unwrap_or(defaults.concurrency),
+            checkpoint: std::env::var("SCAN_CHECKPOINT").ok().map(PathBuf::from),
+        }
+    }
+}
+
+// Everything below `next_block` has been fetched; its logs are the first `logs_bytes` of the logs file
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    filter: Value,
+    from_block: u64,
+    to_block: u64,
+    next_block: u64,
+    logs_bytes: u64,
+}
+
+impl Checkpoint {
+    fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read checkpoint {}: {}", path.display(), e))?;
+        let checkpoint = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Invalid checkpoint {}: {}", path.display(), e))?;
+        Ok(Some(checkpoint))
+    }
+
+    // One JSON log per line, appended as the watermark advances
+    fn logs_path(path: &Path) -> PathBuf {
+        path.with_extension("logs.jsonl")
+    }
+
+    // Logs below the watermark; anything appended after the last save is cut off
+    fn load_logs(&self, path: &Path) -> Result<Vec<Value>> {
+        let logs_path = Self::logs_path(path);
+        let mut file = OpenOptions::new().read(true).write(true).open(&logs_path)
+            .map_err(|e| anyhow!("Failed to open scan logs {}: {}", logs_path.display(), e))?;
+        file.set_len(self.logs_bytes)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        contents
+            .lines()
+            .map(|line| serde_json::from_str(line)
+                .map_err(|e| anyhow!("Invalid scan logs {}: {}", logs_path.display(), e)))
+            .collect()
+    }
+
+    // Write-then-rename so a crash mid-save keeps the previous checkpoint
+    fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)
+            .map_err(|e| anyhow!("Failed to write checkpoint {}: {}", tmp.display(), e))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|e| anyhow!("Failed to write checkpoint {}: {}", path.display(), e))?;
+        Ok(())
+    }
+}
+
+// Whether a failed eth_getLogs is worth retrying over a smaller range
+pub fn is_range_too_large(error: &anyhow::Error) -> bool {
+    let Some(error) = error.downcast_ref::<RpcError>() else {
+        return false;
+    };
+    let message = error.message.to_lowercase();
+    TOO_LARGE_PATTERNS.iter().any(|p| message.contains(p))
+}
+
+// Whether a failed eth_getLogs was throttled (HTTP 429 or -32005 limit exceeded)
+pub fn is_rate_limited(error: &anyhow::Error) -> bool {
+    let Some(error) = error.downcast_ref::<RpcError>() else {
+        return false;
+    };
+    let message = error.message.to_lowercase();
+    error.code == 429 || error.code == -32005 || RATE_LIMIT_PATTERNS.iter().any(|p| message.contains(p))
+}
+
+// Fetch every log matching `filter` in [from_block, to_block] through `fetch(from, to)`,
+// a chunk at a time with several chunks in flight. Logs come back in chain order.
+pub async fn scan_logs<F, Fut>(
+    filter: &Value,
+    from_block: u64,
+    to_block: u64,
+    options: &ScanOptions,
+    fetch: F,
+) -> Result<Vec<Value>>
+where
+    F: Fn(u64, u64) -> Fut,
+    Fut: Future<Output = Result<Vec<Value>>>,
+{
+    if from_block > to_block {
+        return Err(anyhow!("from_block ({}) cannot be greater than to_block ({})", from_block, to_block));
+    }
+
+    let resumed = match &options.checkpoint {
+        Some(path) => Checkpoint::load(path)?
+            .filter(|c| {
+                let matches = &c.filter == filter && c.from_block == from_block && c.to_block == to_block;
+                if !matches {
+                    warn!("⚠️ Checkpoint {} is for a different scan - starting over", path.display());
+                }
+                matches
+            })
+            .and_then(|c| match c.load_logs(path) {
+                Ok(logs) => Some((c, logs)),
+                Err(e) => {
+                    warn!("⚠️ {} - starting over", e);
+                    None
+                }
+            }),
+        None => None,
+    };
+
+    let (mut checkpoint, mut logs) = match resumed {
+        Some((checkpoint, logs)) => {
+            info!("⏯️ Resuming scan at block {} ({} logs already found)", checkpoint.next_block, logs.len());
+            (checkpoint, logs)
+        }
+        None => {
+            if let Some(path) = &options.checkpoint {
+                let logs_path = Checkpoint::logs_path(path);
+                File::create(&logs_path)
+                    .map_err(|e| anyhow!("Failed to create scan logs {}: {}", logs_path.display(), e))?;
+            }
+            let checkpoint = Checkpoint {
+                filter: filter.clone(),
+                from_block,
+                to_block,
+                next_block: from_block,
+                logs_bytes: 0,
+            };
+            (checkpoint, Vec::new())
+        }
+    };
+
+    let total = to_block - from_block + 1;
+    let mut chunk_size = options.chunk_size.max(1);
+    let mut cursor = checkpoint.next_block;
+    // Split halves of rejected ranges and throttled ranges, served before new chunks
+    let mut retries: VecDeque<((u64, u64), Duration)> = VecDeque::new();
+    // Rate-limit refusals since the last successful chunk
+    let mut throttled: u32 = 0;
+    // Finished ranges past the contiguous watermark, keyed by start
+    let mut done: BTreeMap<u64, (u64, Vec<Value>)> = BTreeMap::new();
+    let mut in_flight = FuturesUnordered::new();
+
+    loop {
+        while in_flight.len() < options.concurrency.max(1) {
+            let (range, delay) = match retries.pop_front() {
+                Some(retry) => retry,
+                None if cursor <= to_block => {
+                    let end = cursor.saturating_add(chunk_size - 1).min(to_block);
+                    let range = (cursor, end);
+                    cursor = end + 1;
+                    (range, Duration::ZERO)
+                }
+                None => break,
+            };
+            let request = fetch(range.0, range.1);
+            in_flight.push(async move {
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                (range, request.await)
+            });
+        }
+
+        let Some(((start, end), result)) = in_flight.next().await else {
+            break;
+        };
+
+        match result {
+            Ok(logs) => {
+                throttled = 0;
+                done.insert(start, (end, logs));
+            }
+            Err(e) if is_range_too_large(&e) && end > start => {
+                let mid = start + (end - start) / 2;
+                chunk_size = chunk_size.min(mid - start + 1);
+                warn!("✂️ Blocks {}-{} rejected ({}) - chunk size now {}", start, end, e, chunk_size);
+                retries.push_back(((start, mid), Duration::ZERO));
+                retries.push_back(((mid + 1, end), Duration::ZERO));
+                continue;
+            }
+            Err(e) if is_rate_limited(&e) && throttled < MAX_RATE_LIMIT_RETRIES => {
+                let delay = RATE_LIMIT_BASE_DELAY_MS
+                    .saturating_mul(1 << throttled)
+                    .min(RATE_LIMIT_MAX_DELAY_MS);
+                throttled += 1;
+                warn!("🐢 Blocks {}-{} rate limited ({}) - retrying in {}ms", start, end, e, delay);
+                retries.push_back(((start, end), Duration::from_millis(delay)));
+                continue;
+            }
+            Err(e) => {
+                return Err(anyhow!("eth_getLogs failed for blocks {}-{}: {} (progress saved up to block {})",
+                    start, end, e, checkpoint.next_block));
+            }
+        }
+
+        // Advance the watermark over every contiguous finished range
+        let before = checkpoint.next_block;
+        let found = logs.len();
+        while let Some((end, chunk)) = done.remove(&checkpoint.next_block) {
+            logs.extend(chunk);
+            checkpoint.next_block = end + 1;
+        }
+
+        if checkpoint.next_block > before {
+            let scanned = checkpoint.next_block - from_block;
+            info!("📈 Scanned {}/{} blocks ({:.1}%), {} logs so far",
+                scanned, total, scanned as f64 * 100.0 / total as f64, logs.len());
+            if let Some(path) = &options.checkpoint {
+                // Only the new logs are written; the checkpoint itself stays a few bytes
+                let mut lines = Vec::new();
+                for log in &logs[found..] {
+                    serde_json::to_writer(&mut lines, log)?;
+                    lines.push(b'\n');
+                }
+                let logs_path = Checkpoint::logs_path(path);
+                OpenOptions::new().append(true).open(&logs_path)
+                    .and_then(|mut file| file.write_all(&lines))
+                    .map_err(|e| anyhow!("Failed to append scan logs {}: {}", logs_path.display(), e))?;
+                checkpoint.logs_bytes += lines.len() as u64;
+                checkpoint.save(path)?;
+            }
+        }
+    }
+
+    if let Some(path) = &options.checkpoint {
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(Checkpoint::logs_path(path));
+    }
+
+    logs.sort_by_key(|log| {
+        (
+            log["blockNumber"].as_str().and_then(parse_hex_u64),
+            log["logIndex"].as_str().and_then(parse_hex_u64),
+        )
+    });
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    // One log every 10 blocks; ranges wider than `limit` blocks are refused
+    fn fake_node(limit: u64, calls: Arc<Mutex<Vec<(u64, u64)>>>) -> impl Fn(u64, u64) -> std::future::Ready<Result<Vec<Value>>> {
+        move |from, to| {
+            calls.lock().unwrap().push((from, to));
+            if to - from + 1 > limit {
+                return std::future::ready(Err(anyhow::Error::new(RpcError {
+                    code: -32602,
+                    message: "Block range too large".to_string(),
+                })));
+            }
+            let logs = (from..=to)
+                .filter(|b| b % 10 == 0)
+                .map(|b| serde_json::json!({"blockNumber": format!("0x{:x}", b), "logIndex": "0x0"}))
+                .collect();
+            std::future::ready(Ok(logs))
+        }
+    }
+
+    fn blocks(logs: &[Value]) -> Vec<u64> {
+        logs.iter().filter_map(|l| l["blockNumber"].as_str().and_then(parse_hex_u64)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_shrinks_chunks_on_range_errors() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let options = ScanOptions { chunk_size: 1000, concurrency: 3, checkpoint: None };
+
+        let logs = scan_logs(&serde_json::json!({}), 1, 1000, &options, fake_node(150, calls.clone())).await.unwrap();
+
+        assert_eq!(blocks(&logs), (1..=100).map(|i| i * 10).collect::<Vec<_>>());
+        assert!(calls.lock().unwrap().iter().all(|&(from, to)| from >= 1 && to <= 1000));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits_back_off_without_shrinking_chunks() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let options = ScanOptions { chunk_size: 100, concurrency: 1, checkpoint: None };
+
+        // The first two requests are throttled
+        let refused = Arc::new(AtomicUsize::new(0));
+        let throttling = {
+            let refused = refused.clone();
+            let node = fake_node(u64::MAX, calls.clone());
+            move |from, to| {
+                if refused.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return std::future::ready(Err(anyhow::Error::new(RpcError {
+                        code: 429,
+                        message: "Too many requests, limit exceeded".to_string(),
+                    })));
+                }
+                node(from, to)
+            }
+        };
+
+        let logs = scan_logs(&serde_json::json!({}), 1, 300, &options, throttling).await.unwrap();
+
+        assert_eq!(blocks(&logs), (1..=30).map(|i| i * 10).collect::<Vec<_>>());
+        assert_eq!(*calls.lock().unwrap(), vec![(1, 100), (101, 200), (201, 300)]);
+    }
+
+    #[test]
+    fn test_rate_limit_messages_are_not_range_errors() {
+        let error = |code, message: &str| anyhow::Error::new(RpcError { code, message: message.to_string() });
+
+        assert!(is_range_too_large(&error(-32602, "eth_getLogs block range is too large")));
+        assert!(is_range_too_large(&error(-32005, "query returned more than 10000 results")));
+        for throttled in [
+            error(429, "Too many requests"),
+            error(-32005, "daily request count exceeded, request rate limited"),
+            error(-32000, "More than 100 requests per second, limit exceeded"),
+        ] {
+            assert!(!is_range_too_large(&throttled), "{}", throttled);
+            assert!(is_rate_limited(&throttled), "{}", throttled);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inverted_range_is_an_error() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let result = scan_logs(&serde_json::json!({}), 10, 9, &ScanOptions::default(), fake_node(u64::MAX, calls.clone())).await;
+
+        assert!(result.is_err());
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint() {
+        let path = std::env::temp_dir().join(format!("scan-checkpoint-{}.json", std::process::id()));
+        let filter = serde_json::json!({"address": "0x01"});
+        let options = ScanOptions { chunk_size: 100, concurrency: 1, checkpoint: Some(path.clone()) };
+
+        // Interrupted after the first two chunks
+        let served = Arc::new(AtomicUsize::new(0));
+        let flaky = {
+            let served = served.clone();
+            let node = fake_node(u64::MAX, Arc::default());
+            move |from, to| {
+                if served.fetch_add(1, Ordering::SeqCst) >= 2 {
+                    return std::future::ready(Err(anyhow!("connection reset")));
+                }
+                node(from, to)
+            }
+        };
+        assert!(scan_logs(&filter, 1, 500, &options, flaky).await.is_err());
+
+        // Only the watermark is in the checkpoint; the 20 logs below it are in the logs file
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["next_block"], 201);
+        assert!(saved.get("logs").is_none());
+        let logs_path = Checkpoint::logs_path(&path);
+        assert_eq!(std::fs::read_to_string(&logs_path).unwrap().lines().count(), 20);
+
+        // A crash between appending and saving leaves lines past the watermark
+        OpenOptions::new().append(true).open(&logs_path).unwrap()
+            .write_all(b"{\"blockNumber\":\"0xd2\",\"logIndex\":\"0x0\"}\n").unwrap();
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let logs = scan_logs(&filter, 1, 500, &options, fake_node(u64::MAX, calls.clone())).await.unwrap();
+
+        assert_eq!(blocks(&logs), (1..=50).map(|i| i * 10).collect::<Vec<_>>());
+        assert_eq!(calls.lock().unwrap().first(), Some(&(201, 300)));
+        assert!(!path.exists());
+        assert!(!logs_path.exists());
+    }
+}
@@ -0,0 +1,411 @@
+use anyhow::{Result, anyhow};
+use ethers::types::Address;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::detection::MatchRule;
+use crate::launch_event::{LaunchCall, LaunchEvent};
+use crate::metadata::TokenMetadata;
+
+// What a detector looks at: a mined launch log or a pending factory call
+#[derive(Debug, Clone, Copy)]
+pub enum Subject<'a> {
+    Log(&'a LaunchEvent),
+    Pending(&'a LaunchCall),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate<'a> {
+    pub subject: Subject<'a>,
+    // Account behind the launch once known: verify_caller's initiator or a pending tx's sender
+    pub caller: Option<Address>,
+    // Token views, only for detectors that want_metadata; the creator balance is only filled in once the caller is known
+    pub metadata: Option<&'a TokenMetadata>,
+}
+
+impl<'a> Candidate<'a> {
+    pub fn log(event: &'a LaunchEvent, caller: Option<Address>) -> Self {
+        Self { subject: Subject::Log(event), caller, metadata: None }
+    }
+
+    pub fn pending(call: &'a LaunchCall, sender: Address) -> Self {
+        Self { subject: Subject::Pending(call), caller: Some(sender), metadata: None }
+    }
+
+    pub fn with_metadata(self, metadata: Option<&'a TokenMetadata>) -> Self {
+        Self { metadata, ..self }
+    }
+
+    // Addresses emitted with the launch; a pending call has none yet
+    fn participants(&self) -> Vec<Address> {
+        match self.subject {
+            Subject::Log(event) => event.participants().to_vec(),
+            Subject::Pending(_) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    // `by` is the address that earned the match, if any
+    Accept { rule: MatchRule, by: Option<Address> },
+    Reject(String),
+    // Undecided until the caller is resolved
+    NeedsCaller,
+    // No opinion on this launch
+    Abstain,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Decision::Accept { rule, by: Some(by) } => write!(f, "accepted ({:?} {:?})", rule, by),
+            Decision::Accept { rule, by: None } => write!(f, "accepted ({:?})", rule),
+            Decision::Reject(reason) => write!(f, "rejected: {}", reason),
+            Decision::NeedsCaller => write!(f, "caller unknown"),
+            Decision::Abstain => write!(f, "no detector matched"),
+        }
+    }
+}
+
+// One launch-detection strategy. Decisions must be cheap and synchronous; anything that
+// needs the network (the caller) is requested with NeedsCaller and resolved by the pipeline.
+pub trait LaunchDetector: Send + Sync {
+    fn decide(&self, candidate: &Candidate) -> Decision;
+
+    // Read token metadata before deciding; costs a round trip on every launch, so off by default
+    fn wants_metadata(&self) -> bool {
+        false
+    }
+}
+
+// The profile's wanted/unwanted lists: event addresses first, then the resolved caller
+pub struct CreatorList {
+    wanted: Vec<Address>,
+    unwanted: Vec<Address>,
+}
+
+impl LaunchDetector for CreatorList {
+    fn decide(&self, candidate: &Candidate) -> Decision {
+        // ABI order, wanted wins on the same field
+        for addr in candidate.participants() {
+            if self.wanted.contains(&addr) {
+                return Decision::Accept { rule: MatchRule::EventAddress, by: Some(addr) };
+            }
+            if self.unwanted.contains(&addr) {
+                return Decision::Reject(format!("unwanted {:?} in event", addr));
+            }
+        }
+
+        let rule = match candidate.subject {
+            Subject::Log(_) => MatchRule::VerifiedCaller,
+            Subject::Pending(_) => MatchRule::PendingSender,
+        };
+        match candidate.caller {
+            Some(caller) if self.wanted.contains(&caller) => Decision::Accept { rule, by: Some(caller) },
+            Some(caller) => Decision::Reject(format!("caller {:?} is not wanted", caller)),
+            None => Decision::NeedsCaller,
+        }
+    }
+}
+
+// Sum of per-address scores over the event addresses and the caller (unscored = 0).
+// Abstains when none of them has a score.
+pub struct Reputation {
+    scores: HashMap<Address, i64>,
+    min_score: i64,
+}
+
+impl LaunchDetector for Reputation {
+    fn decide(&self, candidate: &Candidate) -> Decision {
+        let scored = |addr: &Address| self.scores.get(addr).map(|&score| (*addr, score));
+        let found: Vec<(Address, i64)> = candidate
+            .participants()
+            .iter()
+            .chain(candidate.caller.as_ref())
+            .filter_map(scored)
+            .collect();
+        let total: i64 = found.iter().map(|(_, score)| score).sum();
+
+        if total >= self.min_score {
+            let by = found.iter().max_by_key(|(_, score)| *score).map(|(addr, _)| *addr);
+            return Decision::Accept { rule: MatchRule::Reputation, by };
+        }
+        match (candidate.caller, found.is_empty()) {
+            (None, _) => return Decision::NeedsCaller,
+            // Nobody involved has a score; leave it to the other detectors
+            (Some(_), true) => return Decision::Abstain,
+            _ => {}
+        }
+        Decision::Reject(format!("reputation {} below {}", total, self.min_score))
+    }
+}
+
+// Any launch whose event already names its pool; pending calls have no pool yet
+pub struct NewPair;
+
+impl LaunchDetector for NewPair {
+    fn decide(&self, candidate: &Candidate) -> Decision {
+        match candidate.subject {
+            Subject::Log(event) if event.pair.is_zero() => Decision::Reject("launch has no pair".to_string()),
+            Subject::Log(_) => Decision::Accept { rule: MatchRule::NewPair, by: None },
+            Subject::Pending(_) => Decision::Abstain,
+        }
+    }
+}
+
+// AND: any rejection rejects, otherwise wait for the caller while anyone needs it.
+// Abstentions are ignored; the first acceptance supplies the rule.
+pub struct AllOf(Vec<Box<dyn LaunchDetector>>);
+
+impl LaunchDetector for AllOf {
+    fn decide(&self, candidate: &Candidate) -> Decision {
+        let mut accepted = None;
+        let mut needs_caller = false;
+        for detector in &self.0 {
+            match detector.decide(candidate) {
+                Decision::Reject(reason) => return Decision::Reject(reason),
+                Decision::NeedsCaller => needs_caller = true,
+                decision @ Decision::Accept { .. } => { accepted.get_or_insert(decision); }
+                Decision::Abstain => {}
+            }
+        }
+        if needs_caller {
+            return Decision::NeedsCaller;
+        }
+        accepted.unwrap_or(Decision::Abstain)
+    }
+    fn wants_metadata(&self) -> bool {
+        self.0.iter().any(|detector| detector.wants_metadata())
+    }
+}
+
+// OR: the first acceptance wins, then NeedsCaller, then the first rejection
+pub struct AnyOf(Vec<Box<dyn LaunchDetector>>);
+
+impl LaunchDetector for AnyOf {
+    fn decide(&self, candidate: &Candidate) -> Decision {
+        let mut rejected = None;
+        let mut needs_caller = false;
+        for detector in &self.0 {
+            match detector.decide(candidate) {
+                decision @ Decision::Accept { .. } => return decision,
+                Decision::NeedsCaller => needs_caller = true,
+                decision @ Decision::Reject(_) => { rejected.get_or_insert(decision); }
+                Decision::Abstain => {}
+            }
+        }
+        if needs_caller {
+            return Decision::NeedsCaller;
+        }
+        rejected.unwrap_or(Decision::Abstain)
+    }
+    fn wants_metadata(&self) -> bool {
+        self.0.iter().any(|detector| detector.wants_metadata())
+    }
+}
+
+// Priority: the first detector with an opinion decides
+pub struct FirstOf(Vec<Box<dyn LaunchDetector>>);
+
+impl LaunchDetector for FirstOf {
+    fn decide(&self, candidate: &Candidate) -> Decision {
+        self.0
+            .iter()
+            .map(|detector| detector.decide(candidate))
+            .find(|decision| *decision != Decision::Abstain)
+            .unwrap_or(Decision::Abstain)
+    }
+    fn wants_metadata(&self) -> bool {
+        self.0.iter().any(|detector| detector.wants_metadata())
+    }
+}
+
+// Detector tree from a profile's [detector] table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DetectorConfig {
+    #[default]
+    CreatorList,
+    Reputation { scores: HashMap<Address, i64>, min_score: i64 },
+    NewPair,
+    All { detectors: Vec<DetectorConfig> },
+    Any { detectors: Vec<DetectorConfig> },
+    First { detectors: Vec<DetectorConfig> },
+}
+
+impl DetectorConfig {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            DetectorConfig::All { detectors } | DetectorConfig::Any { detectors } | DetectorConfig::First { detectors } => {
+                if detectors.is_empty() {
+                    return Err(anyhow!("composite detector needs at least one member"));
+                }
+                detectors.iter().try_for_each(DetectorConfig::validate)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Whether the wanted/unwanted lists take part in any decision
+    pub fn uses_creator_list(&self) -> bool {
+        match self {
+            DetectorConfig::CreatorList => true,
+            DetectorConfig::All { detectors } | DetectorConfig::Any { detectors } | DetectorConfig::First { detectors } => {
+                detectors.iter().any(DetectorConfig::uses_creator_list)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn build(&self, wanted: &[Address], unwanted: &[Address]) -> Box<dyn LaunchDetector> {
+        let members = |detectors: &[DetectorConfig]| detectors.iter().map(|d| d.build(wanted, unwanted)).collect();
+        match self {
+            DetectorConfig::CreatorList => Box::new(CreatorList { wanted: wanted.to_vec(), unwanted: unwanted.to_vec() }),
+            DetectorConfig::Reputation { scores, min_score } => Box::new(Reputation { scores: scores.clone(), min_score: *min_score }),
+            DetectorConfig::NewPair => Box::new(NewPair),
+            DetectorConfig::All { detectors } => Box::new(AllOf(members(detectors))),
+            DetectorConfig::Any { detectors } => Box::new(AnyOf(members(detectors))),
+            DetectorConfig::First { detectors } => Box::new(FirstOf(members(detectors))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Bytes, U256};
+    use proptest::prelude::*;
+    use serde_json::Value;
+
+    fn addr(byte: u8) -> Address {
+        Address::repeat_byte(byte)
+    }
+
+    fn event(pair: Address) -> LaunchEvent {
+        LaunchEvent {
+            virtual_id: U256::one(),
+            token: addr(0x11),
+            dao: addr(0x22),
+            tba: addr(0x33),
+            ve_token: addr(0x44),
+            pair,
+        }
+    }
+
+    #[test]
+    fn test_composites_follow_and_or_priority() {
+        let (wanted, unwanted) = (vec![addr(0xaa)], vec![addr(0x22)]);
+        let config: DetectorConfig = toml::from_str(r#"
+            kind = "first"
+            detectors = [
+                { kind = "reputation", min_score = 10, scores = { "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb" = 10 } },
+                { kind = "all", detectors = [{ kind = "creator_list" }, { kind = "new_pair" }] },
+            ]
+        "#).unwrap();
+        config.validate().unwrap();
+        let detector = config.build(&wanted, &[]);
+
+        let launch = event(addr(0x55));
+        assert_eq!(detector.decide(&Candidate::log(&launch, None)), Decision::NeedsCaller);
+        assert_eq!(
+            detector.decide(&Candidate::log(&launch, Some(addr(0xbb)))),
+            Decision::Accept { rule: MatchRule::Reputation, by: Some(addr(0xbb)) }
+        );
+        assert_eq!(
+            detector.decide(&Candidate::log(&launch, Some(addr(0xaa)))),
+            Decision::Accept { rule: MatchRule::VerifiedCaller, by: Some(addr(0xaa)) }
+        );
+        // AND: the wanted caller cannot rescue a launch without a pair
+        let unpaired = event(Address::zero());
+        assert!(matches!(detector.decide(&Candidate::log(&unpaired, Some(addr(0xaa)))), Decision::Reject(_)));
+
+        // OR: an unwanted event address loses to a pair-watcher acceptance
+        let any = DetectorConfig::Any { detectors: vec![DetectorConfig::CreatorList, DetectorConfig::NewPair] };
+        let detector = any.build(&wanted, &unwanted);
+        assert_eq!(detector.decide(&Candidate::log(&launch, None)), Decision::Accept { rule: MatchRule::NewPair, by: None });
+        assert!(matches!(detector.decide(&Candidate::log(&unpaired, None)), Decision::Reject(_)));
+
+        assert!(DetectorConfig::All { detectors: vec![] }.validate().is_err());
+    }
+
+    // tests/fixtures/new_persona_corpus.synthetic.json: hand-built TARGET_TOPIC logs in
+    // eth_getLogs form, each labelled with the decoded token and the creator-list outcome
+    // before and after the caller is resolved. Real launches go in tests/fixtures/captures/.
+    #[derive(Deserialize)]
+    struct Corpus {
+        provenance: String,
+        wanted: Vec<Address>,
+        unwanted: Vec<Address>,
+        cases: Vec<Case>,
+    }
+
+    #[derive(Deserialize)]
+    struct Case {
+        name: String,
+        log: Value,
+        token: Option<Address>,
+        classification: String,
+        caller: Option<Address>,
+        with_caller: Option<String>,
+    }
+
+    fn label(decision: &Decision) -> &'static str {
+        match decision {
+            Decision::Accept { rule: MatchRule::EventAddress, .. } => "wanted",
+            Decision::Accept { rule: MatchRule::VerifiedCaller, .. } => "verified",
+            Decision::Accept { .. } => "accepted",
+            Decision::Reject(reason) if reason.starts_with("unwanted") => "unwanted",
+            Decision::Reject(_) => "rejected",
+            Decision::NeedsCaller => "needs_caller",
+            Decision::Abstain => "abstain",
+        }
+    }
+
+    #[test]
+    fn test_golden_corpus() {
+        let corpus: Corpus = serde_json::from_str(include_str!("../tests/fixtures/new_persona_corpus.synthetic.json")).unwrap();
+        assert_eq!(corpus.provenance, "synthetic");
+        let detector = DetectorConfig::CreatorList.build(&corpus.wanted, &corpus.unwanted);
+
+        for case in &corpus.cases {
+            let event = match LaunchEvent::from_log(&case.log) {
+                Ok(event) => event,
+                Err(_) => {
+                    assert_eq!(case.classification, "invalid", "{} failed to decode", case.name);
+                    assert!(case.token.is_none(), "{}", case.name);
+                    continue;
+                }
+            };
+            assert_eq!(Some(event.token), case.token, "{}", case.name);
+            assert_eq!(label(&detector.decide(&Candidate::log(&event, None))), case.classification, "{}", case.name);
+            if let Some(caller) = case.caller {
+                let decision = detector.decide(&Candidate::log(&event, Some(caller)));
+                assert_eq!(Some(label(&decision)), case.with_caller.as_deref(), "{}", case.name);
+            }
+        }
+    }
+
+    proptest! {
+        // A wanted address written anywhere in the payload except a participant slot
+        // must never classify the launch as wanted
+        #[test]
+        fn prop_creator_bytes_outside_participant_slots_never_match(
+            words in proptest::collection::vec(any::<[u8; 32]>(), 6),
+            offset in 0usize..=172,
+        ) {
+            let wanted = addr(0xa7);
+            prop_assume!(!(2..6).any(|slot| offset == slot * 32 + 12));
+
+            let mut data = words.concat();
+            data[offset..offset + 20].copy_from_slice(wanted.as_bytes());
+            let log = serde_json::json!({"topics": [LaunchEvent::topic()], "data": Bytes::from(data)});
+
+            if let Ok(event) = LaunchEvent::from_log(&log) {
+                let detector = DetectorConfig::CreatorList.build(&[wanted], &[]);
+                prop_assert_eq!(detector.decide(&Candidate::log(&event, None)), Decision::NeedsCaller);
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
 use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
 use ethers::types::{Address, U256};
 use std::sync::Arc;
 use tracing::info;
@@ -11,7 +13,7 @@ const UNISWAP_V2_ROUTER: &str = "0x4752ba5dbc23f44d87826276bf6fd6b1c372ad24";
 #[derive(Debug, Clone)]
 pub struct GasConfig {
     pub gas_limit: U256,
-    pub gas_price: Option<U256>,  // For legacy transactions
+    pub gas_price: Option<U256>,  // Legacy transactions only; set by with_legacy_gas_price
     pub max_fee_per_gas: Option<U256>,  // For EIP-1559
     pub max_priority_fee_per_gas: Option<U256>,  // For EIP-1559
 }
@@ -19,10 +21,10 @@ pub struct GasConfig {
 impl Default for GasConfig {
     fn default() -> Self {
         Self {
-            gas_limit: U256::from(1_000_000),  // 500k gas limit
+            gas_limit: U256::from(1_000_000),  // 1M gas limit
             gas_price: None,
-            max_fee_per_gas: Some(U256::from(30_000_000_000u64)),  // 0.02 gwei
-            max_priority_fee_per_gas: Some(U256::from(10_000_000_000u64)),  // 0.1 gwei
+            max_fee_per_gas: Some(U256::from(30_000_000_000u64)),  // 30 gwei
+            max_priority_fee_per_gas: Some(U256::from(10_000_000_000u64)),  // 10 gwei
         }
     }
 }
@@ -60,6 +62,16 @@ impl GasConfig {
         }
     }
     
+    // Named preset from the config profile (see config::GAS_PRESETS)
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "fast" => Some(Self::fast()),
+            "turbo" => Some(Self::turbo()),
+            _ => None,
+        }
+    }
+    
     pub fn turbo() -> Self {
         Self {
             gas_limit: U256::from(500_000),
@@ -75,6 +87,13 @@ abigen!(
     UniswapV2Router,
     r#"[
         function swapExactTokensForTokens(uint amountIn, uint amountOutMin, address[] calldata path, address to, uint deadline) external returns (uint[] memory amounts)
+        function swapExactETHForTokens(uint amountOutMin, address[] calldata path, address to, uint deadline) external payable returns (uint[] memory amounts)
+        function swapExactTokensForETH(uint amountIn, uint amountOutMin, address[] calldata path, address to, uint deadline) external returns (uint[] memory amounts)
+        function getAmountsOut(uint amountIn, address[] calldata path) external view returns (uint[] memory amounts)
+        function getAmountsIn(uint amountOut, address[] calldata path) external view returns (uint[] memory amounts)
+        function getAmountOut(uint amountIn, uint reserveIn, uint reserveOut) external pure returns (uint amountOut)
+        function factory() external pure returns (address)
+        function WETH() external pure returns (address)
     ]"#
 );
 
@@ -84,25 +103,74 @@ abigen!(
     r#"[
         function approve(address spender, uint256 amount) external returns (bool)
         function allowance(address owner, address spender) external view returns (uint256)
+        function balanceOf(address account) external view returns (uint256)
+        function name() external view returns (string)
+        function symbol() external view returns (string)
+        function decimals() external view returns (uint8)
     ]"#
 );
 
+// A mined transaction with the moments it was signed, accepted by the node and confirmed
+#[derive(Debug, Clone)]
+pub struct SentTransaction {
+    pub receipt: TransactionReceipt,
+    pub signed_at: DateTime<Utc>,
+    pub broadcast_at: DateTime<Utc>,
+    pub confirmed_at: DateTime<Utc>,
+}
+
 pub struct UniswapTrader<M> {
     client: Arc<M>,
     router: UniswapV2Router<M>,
+    // The router's factory, read once at construction for pair lookups
+    factory: Address,
+}
+
+// Shares the client and router; lets each buy run in its own task
+impl<M> Clone for UniswapTrader<M> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            router: self.router.clone(),
+            factory: self.factory,
+        }
+    }
 }
 
 impl<M: Middleware + 'static> UniswapTrader<M> {
-    pub fn new(client: Arc<M>) -> Result<Self> {
+    pub async fn new(client: Arc<M>) -> Result<Self> {
         let router_address: Address = UNISWAP_V2_ROUTER.parse()?;
+        Self::with_router(client, router_address).await
+    }
+    
+    // Use a router other than the default Base deployment
+    pub async fn with_router(client: Arc<M>, router_address: Address) -> Result<Self> {
         let router = UniswapV2Router::new(router_address, client.clone());
+        let factory = router
+            .factory()
+            .call()
+            .await
+            .map_err(|e| anyhow!("Failed to read router factory: {}", e))?;
         
         Ok(Self {
             client,
             router,
+            factory,
         })
     }
     
+    pub fn client(&self) -> Arc<M> {
+        self.client.clone()
+    }
+    
+    pub fn router_address(&self) -> Address {
+        self.router.address()
+    }
+    
+    pub fn factory_address(&self) -> Address {
+        self.factory
+    }
+    
     // Approve token spending
     pub async fn approve_token(
         &self, 
@@ -113,20 +181,10 @@ impl<M: Middleware + 'static> UniswapTrader<M> {
         info!("✅ Approving token {} for amount: {}", token_address, amount);
         
         let token = ERC20Token::new(token_address, self.client.clone());
-        let router_address: Address = UNISWAP_V2_ROUTER.parse()?;
+        let router_address = self.router.address();
         
         let mut tx = token.approve(router_address, amount);
-        
-        // Apply gas configuration
-        if let Some(gas_config) = gas_config {
-            tx = tx.gas(gas_config.gas_limit);
-            
-            if let Some(gas_price) = gas_config.gas_price {
-                tx = tx.gas_price(gas_price);
-            } else if let Some(max_fee) = gas_config.max_fee_per_gas {
-                tx = tx.gas_price(max_fee);  // For simplicity, using gas_price for EIP-1559
-            }
-        }
+        apply_gas_config(&mut tx.tx, gas_config)?;
         
         let pending_tx = tx.send().await?;
         info!("📤 Approval transaction sent: {:?}", pending_tx.tx_hash());
@@ -140,7 +198,7 @@ impl<M: Middleware + 'static> UniswapTrader<M> {
     // Check token allowance
     pub async fn check_allowance(&self, token_address: Address, owner: Address) -> Result<U256> {
         let token = ERC20Token::new(token_address, self.client.clone());
-        let router_address: Address = UNISWAP_V2_ROUTER.parse()?;
+        let router_address = self.router.address();
         
         let allowance = token.allowance(owner, router_address).call().await?;
         info!("🔍 Current allowance: {}", allowance);
@@ -158,33 +216,292 @@ impl<M: Middleware + 'static> UniswapTrader<M> {
         deadline: U256,
         gas_config: Option<GasConfig>
     ) -> Result<TransactionReceipt> {
+        let sent = self
+            .swap_exact_tokens_for_tokens_timed(amount_in, amount_out_min, path, to, deadline, gas_config)
+            .await?;
+        Ok(sent.receipt)
+    }
+    
+    // Same swap, reporting when it was signed, broadcast and confirmed
+    pub async fn swap_exact_tokens_for_tokens_timed(
+        &self,
+        amount_in: U256,
+        amount_out_min: U256,
+        path: Vec<Address>,
+        to: Address,
+        deadline: U256,
+        gas_config: Option<GasConfig>
+    ) -> Result<SentTransaction> {
         info!("🔄 Swapping exact tokens: {} for minimum {} tokens", amount_in, amount_out_min);
         info!("📍 Path: {:?}", path);
         
         let mut tx = self.router
             .swap_exact_tokens_for_tokens(amount_in, amount_out_min, path, to, deadline);
+        apply_gas_config(&mut tx.tx, gas_config)?;
         
-        // Apply gas configuration
-        if let Some(gas_config) = gas_config {
-            tx = tx.gas(gas_config.gas_limit);
-            
-            if let Some(gas_price) = gas_config.gas_price {
-                tx = tx.gas_price(gas_price);
-            } else if let Some(max_fee) = gas_config.max_fee_per_gas {
-                tx = tx.gas_price(max_fee);  // For simplicity, using gas_price for EIP-1559
-            }
+        self.send_timed(tx.tx, "Swap").await
+    }
+    
+    // Token -> token over `token_in`/`token_out`'s own pair, expiring in 5 minutes
+    pub async fn swap_exact_tokens_for_tokens_direct(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        amount_out_min: U256,
+        to: Address,
+        gas_config: Option<GasConfig>
+    ) -> Result<TransactionReceipt> {
+        self.swap_tokens_custom_path(vec![token_in, token_out], amount_in, amount_out_min, to, gas_config).await
+    }
+    
+    // Token -> token along any router path, expiring in 5 minutes
+    pub async fn swap_tokens_custom_path(
+        &self,
+        path: Vec<Address>,
+        amount_in: U256,
+        amount_out_min: U256,
+        to: Address,
+        gas_config: Option<GasConfig>
+    ) -> Result<TransactionReceipt> {
+        if path.len() < 2 {
+            return Err(anyhow!("Swap path needs at least 2 tokens, got {}", path.len()));
         }
+        let deadline = get_deadline_from_now(300);
+        self.swap_exact_tokens_for_tokens(amount_in, amount_out_min, path, to, deadline, gas_config).await
+    }
+    
+    // Swap exact native ETH for tokens (router wraps it through WETH)
+    pub async fn swap_eth_for_tokens(
+        &self,
+        eth_amount: U256,
+        token: Address,
+        amount_out_min: U256,
+        to: Address,
+        deadline: U256,
+        gas_config: Option<GasConfig>
+    ) -> Result<TransactionReceipt> {
+        info!("🔄 Swapping {} wei ETH for minimum {} of {:?}", eth_amount, amount_out_min, token);
         
-        let pending_tx = tx.send().await?;
-        info!("📤 Swap transaction sent: {:?}", pending_tx.tx_hash());
+        let path = vec![self.weth().await?, token];
+        let mut tx = self.router
+            .swap_exact_eth_for_tokens(amount_out_min, path, to, deadline)
+            .value(eth_amount);
+        apply_gas_config(&mut tx.tx, gas_config)?;
+        
+        Ok(self.send_timed(tx.tx, "Swap").await?.receipt)
+    }
+    
+    // Swap exact tokens for native ETH; the router must already be approved for `amount_in`
+    pub async fn swap_tokens_for_eth(
+        &self,
+        token: Address,
+        amount_in: U256,
+        amount_out_min: U256,
+        to: Address,
+        deadline: U256,
+        gas_config: Option<GasConfig>
+    ) -> Result<TransactionReceipt> {
+        info!("🔄 Swapping {} of {:?} for minimum {} wei ETH", amount_in, token, amount_out_min);
         
-        let receipt = pending_tx.await?.ok_or_else(|| anyhow!("Swap transaction failed"))?;
-        info!("✅ Swap confirmed in block: {}", receipt.block_number.unwrap_or_default());
+        let path = vec![token, self.weth().await?];
+        let mut tx = self.router
+            .swap_exact_tokens_for_eth(amount_in, amount_out_min, path, to, deadline);
+        apply_gas_config(&mut tx.tx, gas_config)?;
         
-        Ok(receipt)
+        Ok(self.send_timed(tx.tx, "Swap").await?.receipt)
+    }
+    
+    // Wrapped native token the router swaps ETH through
+    pub async fn weth(&self) -> Result<Address> {
+        self.router
+            .weth()
+            .call()
+            .await
+            .map_err(|e| anyhow!("Failed to read router WETH: {}", e))
+    }
+    
+    // Output amount at every hop of `path` for an exact input
+    pub async fn get_amounts_out(&self, amount_in: U256, path: Vec<Address>) -> Result<Vec<U256>> {
+        self.router
+            .get_amounts_out(amount_in, path)
+            .call()
+            .await
+            .map_err(|e| anyhow!("Failed to quote getAmountsOut: {}", e))
+    }
+    
+    // Input amount needed at every hop of `path` for an exact output
+    pub async fn get_amounts_in(&self, amount_out: U256, path: Vec<Address>) -> Result<Vec<U256>> {
+        self.router
+            .get_amounts_in(amount_out, path)
+            .call()
+            .await
+            .map_err(|e| anyhow!("Failed to quote getAmountsIn: {}", e))
+    }
+    
+    // Quote over the direct `token_in`/`token_out` pair
+    pub async fn get_token_to_token_quote(&self, token_in: Address, token_out: Address, amount_in: U256) -> Result<Vec<U256>> {
+        self.get_amounts_out(amount_in, vec![token_in, token_out]).await
+    }
+    
+    // (name, symbol, decimals)
+    pub async fn get_token_info(&self, token_address: Address) -> Result<(String, String, u8)> {
+        let token = ERC20Token::new(token_address, self.client.clone());
+        let (name, symbol, decimals) = (token.name(), token.symbol(), token.decimals());
+        let info = tokio::try_join!(name.call(), symbol.call(), decimals.call())
+            .map_err(|e| anyhow!("Failed to read token info for {:?}: {}", token_address, e))?;
+        Ok(info)
+    }
+    
+    pub async fn get_token_balance(&self, token_address: Address, owner: Address) -> Result<U256> {
+        let token = ERC20Token::new(token_address, self.client.clone());
+        token
+            .balance_of(owner)
+            .call()
+            .await
+            .map_err(|e| anyhow!("Failed to read balance of {:?}: {}", token_address, e))
+    }
+    
+    // Fill, sign and broadcast as separate steps so each one can be timed
+    async fn send_timed(&self, mut tx: TypedTransaction, label: &str) -> Result<SentTransaction> {
+        let from = self.client
+            .default_sender()
+            .ok_or_else(|| anyhow!("{} needs a client with a sender account", label))?;
+        tx.set_from(from);
+        self.client
+            .fill_transaction(&mut tx, None)
+            .await
+            .map_err(|e| anyhow!("Failed to prepare {} transaction: {}", label, e))?;
+        
+        let signature = self.client
+            .sign_transaction(&tx, from)
+            .await
+            .map_err(|e| anyhow!("Failed to sign {} transaction: {}", label, e))?;
+        let signed_at = Utc::now();
+        
+        let pending_tx = self.client
+            .send_raw_transaction(tx.rlp_signed(&signature))
+            .await
+            .map_err(|e| anyhow!("Failed to send {} transaction: {}", label, e))?;
+        let broadcast_at = Utc::now();
+        info!("📤 {} transaction sent: {:?}", label, pending_tx.tx_hash());
+        
+        let receipt = pending_tx.await?.ok_or_else(|| anyhow!("{} transaction failed", label))?;
+        let confirmed_at = Utc::now();
+        info!("✅ {} confirmed in block: {}", label, receipt.block_number.unwrap_or_default());
+        
+        Ok(SentTransaction {
+            receipt,
+            signed_at,
+            broadcast_at,
+            confirmed_at,
+        })
     }
 }
 
+// Gas limit and fees from the config as a typed EIP-1559 request, or a legacy one when
+// the config carries a gas_price (with_legacy_gas_price). Unset fees are estimated on fill.
+fn apply_gas_config(tx: &mut TypedTransaction, gas_config: Option<GasConfig>) -> Result<()> {
+    let Some(gas_config) = gas_config else {
+        return Ok(());
+    };
+    
+    if let Some(gas_price) = gas_config.gas_price {
+        let mut legacy: TransactionRequest = tx.clone().into();
+        legacy.gas_price = Some(gas_price);
+        *tx = TypedTransaction::Legacy(legacy);
+    } else {
+        if let (Some(max_fee), Some(priority_fee)) = (gas_config.max_fee_per_gas, gas_config.max_priority_fee_per_gas) {
+            if priority_fee > max_fee {
+                return Err(anyhow!("Priority fee {} exceeds max fee {}", priority_fee, max_fee));
+            }
+        }
+        let mut request: Eip1559TransactionRequest = tx.clone().into();
+        request.max_fee_per_gas = gas_config.max_fee_per_gas;
+        request.max_priority_fee_per_gas = gas_config.max_priority_fee_per_gas;
+        *tx = TypedTransaction::Eip1559(request);
+    }
+    tx.set_gas(gas_config.gas_limit);
+    
+    Ok(())
+}
+
+// Minimum acceptable output after `slippage` percent, e.g. 5.0 keeps 95% of the quote
+pub fn apply_slippage(amount: U256, slippage: f64) -> Result<U256> {
+    if !(0.0..100.0).contains(&slippage) {
+        return Err(anyhow!("Slippage must be in [0, 100), got {}", slippage));
+    }
+    let kept_bps = 10_000 - (slippage * 100.0).round() as u64;
+    Ok(amount * kept_bps / 10_000)
+}
+
+// Buy `token` with `eth_amount` ETH (decimal string) at the quoted price less `slippage` percent
+pub async fn quick_buy_token<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    token: Address,
+    eth_amount: &str,
+    slippage: f64,
+    recipient: Address
+) -> Result<TransactionReceipt> {
+    let amount_in = parse_ether(eth_amount)?;
+    let path = vec![trader.weth().await?, token];
+    let quote = trader.get_amounts_out(amount_in, path).await?;
+    let amount_out_min = apply_slippage(*quote.last().ok_or_else(|| anyhow!("Empty quote"))?, slippage)?;
+    
+    trader
+        .swap_eth_for_tokens(amount_in, token, amount_out_min, recipient, get_deadline_from_now(300), Some(GasConfig::turbo()))
+        .await
+}
+
+// Token -> token at the quoted price less `slippage` percent, direct or routed through WETH.
+// The router must already be approved for `amount_in`.
+pub async fn quick_swap_tokens<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    slippage: f64,
+    recipient: Address,
+    via_weth: bool
+) -> Result<TransactionReceipt> {
+    let path = if via_weth {
+        vec![token_in, trader.weth().await?, token_out]
+    } else {
+        vec![token_in, token_out]
+    };
+    let quote = trader.get_amounts_out(amount_in, path.clone()).await?;
+    let amount_out_min = apply_slippage(*quote.last().ok_or_else(|| anyhow!("Empty quote"))?, slippage)?;
+    
+    trader
+        .swap_tokens_custom_path(path, amount_in, amount_out_min, recipient, Some(GasConfig::turbo()))
+        .await
+}
+
+// quick_swap_tokens, first approving exactly `amount_in` if the sender's allowance is short
+pub async fn quick_swap_tokens_with_approval<M: Middleware + 'static>(
+    trader: &UniswapTrader<M>,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    slippage: f64,
+    recipient: Address,
+    via_weth: bool
+) -> Result<TransactionReceipt> {
+    let owner = trader.client
+        .default_sender()
+        .ok_or_else(|| anyhow!("Approval needs a client with a sender account"))?;
+    if trader.check_allowance(token_in, owner).await? < amount_in {
+        trader.approve_token(token_in, amount_in, Some(GasConfig::fast())).await?;
+    }
+    
+    quick_swap_tokens(trader, token_in, token_out, amount_in, slippage, recipient, via_weth).await
+}
+
+// Decimal ETH amount to wei, e.g. "0.1"
+pub fn parse_ether(amount: &str) -> Result<U256> {
+    ethers::utils::parse_ether(amount).map_err(|e| anyhow!("Invalid ETH amount {:?}: {}", amount, e))
+}
+
 // Utility functions
 pub fn get_deadline_from_now(seconds: u64) -> U256 {
     let now = std::time::SystemTime::now()
@@ -197,6 +514,10 @@ pub fn get_deadline_from_now(seconds: u64) -> U256 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock_node::{output, MockNode};
+    use ethers::abi::{AbiDecode, Token};
+    use ethers::contract::EthCall;
+    use std::time::Duration;
     
     #[test]
     fn test_gas_config() {
@@ -208,4 +529,101 @@ mod tests {
             .with_eip1559_gas(10_000_000_000, 5_000_000_000);
         assert_eq!(custom_config.gas_limit, U256::from(1_000_000));
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_gas_presets_by_name() {
+        assert_eq!(GasConfig::preset("turbo").map(|c| c.gas_limit), Some(GasConfig::turbo().gas_limit));
+        assert_eq!(GasConfig::preset("fast").map(|c| c.gas_limit), Some(GasConfig::fast().gas_limit));
+        assert!(GasConfig::preset("default").is_some());
+        assert!(GasConfig::preset("warp").is_none());
+    }
+    
+    #[test]
+    fn test_apply_gas_config_picks_the_transaction_type() {
+        // A contract call as FunctionCall builds it, with calldata and a sender
+        let base = || -> TypedTransaction {
+            Eip1559TransactionRequest::new()
+                .from(Address::repeat_byte(0x01))
+                .to(Address::repeat_byte(0x02))
+                .data(vec![0xde, 0xad])
+                .into()
+        };
+
+        let mut tx = base();
+        apply_gas_config(&mut tx, Some(GasConfig::new().with_gas_limit(300_000).with_eip1559_gas(30, 2))).unwrap();
+        let TypedTransaction::Eip1559(request) = &tx else {
+            panic!("expected an EIP-1559 request, got {:?}", tx);
+        };
+        assert_eq!((request.max_fee_per_gas, request.max_priority_fee_per_gas), (Some(30.into()), Some(2.into())));
+        assert_eq!(request.gas, Some(300_000.into()));
+        assert_eq!(request.data.as_ref().map(|d| d.to_vec()), Some(vec![0xde, 0xad]));
+
+        let mut tx = base();
+        apply_gas_config(&mut tx, Some(GasConfig::new().with_legacy_gas_price(7))).unwrap();
+        let TypedTransaction::Legacy(request) = &tx else {
+            panic!("expected a legacy request, got {:?}", tx);
+        };
+        assert_eq!((request.gas_price, request.gas), (Some(7.into()), Some(1_000_000.into())));
+        assert_eq!((request.from, request.to.clone()), (Some(Address::repeat_byte(0x01)), Some(Address::repeat_byte(0x02).into())));
+
+        // Priority above the cap is rejected and the request left alone
+        let mut tx = base();
+        assert!(apply_gas_config(&mut tx, Some(GasConfig::new().with_eip1559_gas(2, 30))).is_err());
+        assert_eq!(tx, base());
+
+        let mut tx = base();
+        apply_gas_config(&mut tx, None).unwrap();
+        assert_eq!(tx, base());
+    }
+
+    #[tokio::test]
+    async fn test_quick_helpers_send_typed_eip1559_with_slippage_and_approval() {
+        let (router, weth, token_in, token_out) =
+            (Address::repeat_byte(0x4e), Address::repeat_byte(0x42), Address::repeat_byte(0xaa), Address::repeat_byte(0xbb));
+        let node = MockNode::start().await;
+        node.on_call(router, FactoryCall::selector(), output(&[Token::Address(Address::repeat_byte(0xfa))]));
+        node.on_call(router, WethCall::selector(), output(&[Token::Address(weth)]));
+        node.on_call(router, GetAmountsOutCall::selector(), output(&[Token::Array(vec![Token::Uint(1.into()), Token::Uint(1_000.into())])]));
+        node.on_call(token_in, AllowanceCall::selector(), output(&[Token::Uint(0.into())]));
+        
+        // Anvil's first dev key
+        let wallet: LocalWallet = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let recipient = wallet.address();
+        let provider = Provider::<Ws>::connect(node.url()).await.unwrap().interval(Duration::from_millis(10));
+        let trader = UniswapTrader::with_router(Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(8453u64))), router).await.unwrap();
+        
+        quick_buy_token(&trader, token_out, "0.1", 5.0, recipient).await.unwrap();
+        quick_swap_tokens_with_approval(&trader, token_in, token_out, 500.into(), 2.5, recipient, true).await.unwrap();
+        
+        let sent = node.sent_transactions();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0].tx.value(), Some(&parse_ether("0.1").unwrap()));
+        let buy = SwapExactETHForTokensCall::decode(sent[0].tx.data().unwrap()).unwrap();
+        assert_eq!((buy.amount_out_min, buy.path, buy.to), (950.into(), vec![weth, token_out], recipient));
+        
+        assert_eq!(sent[1].tx.to_addr(), Some(&token_in));
+        assert_eq!(ApproveCall::decode(sent[1].tx.data().unwrap()).unwrap().amount, 500.into());
+        let swap = SwapExactTokensForTokensCall::decode(sent[2].tx.data().unwrap()).unwrap();
+        assert_eq!((swap.amount_in, swap.amount_out_min, swap.path), (500.into(), 975.into(), vec![token_in, weth, token_out]));
+        
+        assert!(apply_slippage(1_000.into(), 100.0).is_err());
+        
+        // Both fee fields reach the wire; legacy only when asked for
+        let fees = |tx: &TypedTransaction| match tx {
+            TypedTransaction::Eip1559(inner) => (inner.max_fee_per_gas, inner.max_priority_fee_per_gas),
+            other => panic!("expected an EIP-1559 transaction, got {:?}", other),
+        };
+        let (turbo, fast) = (GasConfig::turbo(), GasConfig::fast());
+        assert_eq!(fees(&sent[0].tx), (turbo.max_fee_per_gas, turbo.max_priority_fee_per_gas));
+        assert_eq!(fees(&sent[1].tx), (fast.max_fee_per_gas, fast.max_priority_fee_per_gas));
+        assert_eq!(sent[1].tx.gas(), Some(&fast.gas_limit));
+        
+        let legacy = GasConfig::new().with_legacy_gas_price(3_000_000_000);
+        trader.approve_token(token_in, 1.into(), Some(legacy)).await.unwrap();
+        let sent = node.sent_transactions();
+        assert!(matches!(&sent[3].tx, TypedTransaction::Legacy(tx) if tx.gas_price == Some(3_000_000_000u64.into())));
+        
+        let inverted = GasConfig::new().with_eip1559_gas(1_000_000_000, 2_000_000_000);
+        assert!(trader.approve_token(token_in, 1.into(), Some(inverted)).await.is_err());
+    }
+}